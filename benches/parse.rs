@@ -0,0 +1,87 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use eventsource_stream::Eventsource;
+use futures::executor::block_on;
+use futures::stream::{self, StreamExt};
+
+const SIZES: [usize; 3] = [1 << 20, 2 << 20, 4 << 20];
+
+fn small_events(size: usize) -> String {
+    let mut input = String::with_capacity(size + 64);
+    let mut id = 0;
+    while input.len() < size {
+        input.push_str(&format!(
+            "id: {}\nevent: tick\ndata: {{\"price\": 42}}\n\n",
+            id
+        ));
+        id += 1;
+    }
+    input
+}
+
+fn count_events(chunks: Vec<&[u8]>) -> usize {
+    block_on(
+        stream::iter(chunks.into_iter().map(Ok::<_, ()>))
+            .eventsource()
+            .fold(0, |count, event| async move {
+                black_box(event.unwrap());
+                count + 1
+            }),
+    )
+}
+
+fn single_chunk(c: &mut Criterion) {
+    let mut group = c.benchmark_group("single_chunk");
+    for size in SIZES {
+        let input = small_events(size);
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &input, |b, input| {
+            b.iter(|| count_events(vec![input.as_bytes()]))
+        });
+    }
+    group.finish();
+}
+
+fn many_chunks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("8k_chunks");
+    for size in SIZES {
+        let input = small_events(size);
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &input, |b, input| {
+            b.iter(|| count_events(input.as_bytes().chunks(8 * 1024).collect()))
+        });
+    }
+    group.finish();
+}
+
+fn long_data_line(c: &mut Criterion) {
+    let mut group = c.benchmark_group("long_data_line");
+    for size in SIZES {
+        let input = format!("data: {}\n\n", "x".repeat(size));
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &input, |b, input| {
+            b.iter(|| count_events(vec![input.as_bytes()]))
+        });
+    }
+    group.finish();
+}
+
+fn long_data_line_chunks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("long_data_line_8k_chunks");
+    for size in SIZES {
+        let input = format!("data: {}\n\n", "x".repeat(size));
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &input, |b, input| {
+            b.iter(|| count_events(input.as_bytes().chunks(8 * 1024).collect()))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    single_chunk,
+    many_chunks,
+    long_data_line,
+    long_data_line_chunks
+);
+criterion_main!(benches);
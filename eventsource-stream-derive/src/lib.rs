@@ -0,0 +1,106 @@
+//! Derive macro for the `SseEvent` trait of
+//! [eventsource-stream](https://docs.rs/eventsource-stream), enabled with its `derive` feature.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields, LitStr, Variant};
+
+/// Map events to the variants of an enum by their `event` field
+///
+/// Every variant matches events whose type is the variant name, or the name given with
+/// `#[sse(event = "...")]`. Events without an `event` field have the type `message`. Unit
+/// variants ignore the data of the event, variants with a single field deserialize it from JSON.
+///
+/// ```ignore
+/// #[derive(SseEvent)]
+/// enum Update {
+///     #[sse(event = "add")]
+///     Add(Item),
+///     #[sse(event = "remove")]
+///     Remove(u64),
+///     #[sse(event = "ping")]
+///     Ping,
+/// }
+/// ```
+#[proc_macro_derive(SseEvent, attributes(sse))]
+pub fn derive_sse_event(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let variants = match &input.data {
+        Data::Enum(data) => &data.variants,
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "SseEvent can only be derived for enums",
+            ))
+        }
+    };
+
+    let arms = variants
+        .iter()
+        .map(|variant| {
+            let event_type = event_type(variant)?;
+            let ident = &variant.ident;
+            let body =
+                match &variant.fields {
+                    Fields::Unit => quote!(::core::result::Result::Ok(Self::#ident)),
+                    Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                        quote!(event.json().map(Self::#ident))
+                    }
+                    fields => return Err(Error::new_spanned(
+                        fields,
+                        "SseEvent variants must be unit variants or have a single unnamed field",
+                    )),
+                };
+            Ok(quote!(#event_type => ::core::option::Option::Some(#body),))
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::eventsource_stream::SseEvent for #ident #ty_generics #where_clause {
+            fn from_event(
+                event: &::eventsource_stream::Event,
+            ) -> ::core::option::Option<
+                ::core::result::Result<Self, ::eventsource_stream::__private::serde_json::Error>,
+            > {
+                let event_type = if event.event.is_empty() {
+                    "message"
+                } else {
+                    event.event.as_str()
+                };
+                match event_type {
+                    #(#arms)*
+                    _ => ::core::option::Option::None,
+                }
+            }
+        }
+    })
+}
+
+/// The value of `#[sse(event = "...")]`, or the variant name
+fn event_type(variant: &Variant) -> syn::Result<LitStr> {
+    let mut event_type = None;
+    for attr in variant
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("sse"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("event") {
+                event_type = Some(meta.value()?.parse::<LitStr>()?);
+                Ok(())
+            } else {
+                Err(meta.error("unknown sse attribute, expected `event`"))
+            }
+        })?;
+    }
+    Ok(event_type.unwrap_or_else(|| LitStr::new(&variant.ident.to_string(), variant.ident.span())))
+}
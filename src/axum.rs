@@ -0,0 +1,232 @@
+use crate::body::{sse_response, EventBody};
+use crate::encoder::{encode_comment, EncodeError};
+use crate::event::Event;
+use axum_core::body::Body as AxumBody;
+use axum_core::response::{IntoResponse, Response};
+use bytes::Bytes;
+use core::future::Future;
+use core::pin::Pin;
+use core::time::Duration;
+use futures_core::ready;
+use futures_core::stream::Stream;
+use futures_core::task::{Context, Poll};
+use http_body::{Body, Frame};
+use pin_project::pin_project;
+use tokio::time::{Instant, Sleep};
+
+/// An axum response streaming events as `text/event-stream`
+///
+/// Every event is sent in its own frame, so it is flushed to the client right away. With
+/// [`Sse::with_keep_alive`] a comment is sent whenever no event was sent for a while, which keeps
+/// proxies from closing an idle connection. This needs a tokio runtime with the time driver.
+///
+/// ```
+/// # use eventsource_stream::{Event, KeepAlive, Sse};
+/// async fn handler() -> Sse<futures::stream::Iter<std::vec::IntoIter<Event>>> {
+///     let events = vec![Event {
+///         event: "greeting".to_string(),
+///         data: "hello".to_string(),
+///         ..Default::default()
+///     }];
+///     Sse::new(futures::stream::iter(events)).with_keep_alive(KeepAlive::default())
+/// }
+/// ```
+pub struct Sse<S> {
+    events: S,
+    keep_alive: Option<KeepAlive>,
+}
+
+impl<S> Sse<S> {
+    /// Send every event of `events`
+    pub fn new(events: S) -> Self {
+        Self {
+            events,
+            keep_alive: None,
+        }
+    }
+
+    /// Send keep-alive comments while no event is sent
+    pub fn with_keep_alive(mut self, keep_alive: KeepAlive) -> Self {
+        self.keep_alive = Some(keep_alive);
+        self
+    }
+}
+
+impl<S> IntoResponse for Sse<S>
+where
+    S: Stream<Item = Event> + Send + 'static,
+{
+    fn into_response(self) -> Response {
+        sse_response(AxumBody::new(SseBody {
+            body: EventBody::new(self.events),
+            keep_alive: self.keep_alive.map(KeepAlive::into_timer),
+        }))
+    }
+}
+
+/// Configuration of the keep-alive comments of an [`Sse`] response
+#[derive(Debug, Clone)]
+pub struct KeepAlive {
+    interval: Duration,
+    comment: String,
+}
+
+impl Default for KeepAlive {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(15),
+            comment: String::new(),
+        }
+    }
+}
+
+impl KeepAlive {
+    /// Send an empty comment after 15 seconds without events
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Send the comment after `interval` without events
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Send `comment` as keep-alive comment
+    pub fn with_comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = comment.into();
+        self
+    }
+
+    fn into_timer(self) -> KeepAliveTimer {
+        let mut comment = String::new();
+        encode_comment(&self.comment, &mut comment);
+        KeepAliveTimer {
+            sleep: tokio::time::sleep(self.interval),
+            interval: self.interval,
+            comment: comment.into(),
+        }
+    }
+}
+
+#[pin_project]
+struct KeepAliveTimer {
+    #[pin]
+    sleep: Sleep,
+    interval: Duration,
+    comment: Bytes,
+}
+
+impl KeepAliveTimer {
+    fn reset(self: Pin<&mut Self>) {
+        let this = self.project();
+        this.sleep.reset(Instant::now() + *this.interval);
+    }
+
+    fn poll_comment(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Bytes> {
+        ready!(self.as_mut().project().sleep.poll(cx));
+        self.as_mut().reset();
+        Poll::Ready(self.comment.clone())
+    }
+}
+
+#[pin_project]
+struct SseBody<S> {
+    #[pin]
+    body: EventBody<S>,
+    #[pin]
+    keep_alive: Option<KeepAliveTimer>,
+}
+
+impl<S> Body for SseBody<S>
+where
+    S: Stream<Item = Event>,
+{
+    type Data = Bytes;
+    type Error = EncodeError;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let mut this = self.project();
+        if let Poll::Ready(frame) = this.body.poll_frame(cx) {
+            if let Some(keep_alive) = this.keep_alive.as_mut().as_pin_mut() {
+                keep_alive.reset();
+            }
+            return Poll::Ready(frame);
+        }
+        match this.keep_alive.as_pin_mut() {
+            Some(keep_alive) => keep_alive
+                .poll_comment(cx)
+                .map(|comment| Some(Ok(Frame::data(comment)))),
+            None => Poll::Pending,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::prelude::*;
+    use http::header::{CACHE_CONTROL, CONTENT_TYPE};
+    use http_body_util::BodyExt;
+
+    #[tokio::test(start_paused = true)]
+    async fn sse_response() {
+        let event = Event {
+            data: "hello".to_string(),
+            ..Default::default()
+        };
+        let events = stream::iter(vec![event])
+            .chain(stream::once(async {
+                tokio::time::sleep(Duration::from_secs(25)).await;
+                Event {
+                    data: "bye".to_string(),
+                    ..Default::default()
+                }
+            }))
+            .boxed();
+        let response = Sse::new(events)
+            .with_keep_alive(KeepAlive::new().with_interval(Duration::from_secs(10)))
+            .into_response();
+        assert_eq!(response.headers()[CONTENT_TYPE], "text/event-stream");
+        assert_eq!(response.headers()[CACHE_CONTROL], "no-cache");
+
+        let mut body = response.into_body();
+        let mut frames = Vec::new();
+        while let Some(frame) = body.frame().await {
+            let data = frame.unwrap().into_data().unwrap();
+            frames.push((
+                tokio::time::Instant::now(),
+                String::from_utf8(data.to_vec()).unwrap(),
+            ));
+        }
+        let start = frames[0].0;
+        let frames = frames
+            .into_iter()
+            .map(|(time, data)| ((time - start).as_secs(), data))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            frames,
+            vec![
+                (0, "data: hello\n\n".to_string()),
+                (10, ":\n".to_string()),
+                (20, ":\n".to_string()),
+                (25, "data: bye\n\n".to_string()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn without_keep_alive() {
+        let body = Sse::new(stream::empty())
+            .into_response()
+            .into_body()
+            .collect()
+            .await
+            .unwrap()
+            .to_bytes();
+        assert!(body.is_empty());
+    }
+}
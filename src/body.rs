@@ -0,0 +1,215 @@
+use crate::encoder::{encode_event, EncodeError};
+use crate::event::Event;
+use crate::event_stream::EventStream;
+use bytes::{Buf, Bytes};
+use core::pin::Pin;
+use futures_core::ready;
+use futures_core::stream::Stream;
+use futures_core::task::{Context, Poll};
+use http::header::{CACHE_CONTROL, CONTENT_TYPE};
+use http::{HeaderMap, HeaderValue, Response};
+use http_body::{Body, Frame};
+use pin_project::pin_project;
+
+/// A Stream of the data frames of an [`http_body::Body`], such as a `hyper::body::Incoming`
+///
+/// Trailers are not part of the stream but kept to be read with [`BodyStream::trailers`] once the
+/// stream ended.
+#[pin_project]
+pub struct BodyStream<B> {
+    #[pin]
+    body: B,
+    trailers: Option<HeaderMap>,
+}
+
+impl<B> BodyStream<B> {
+    /// Stream the data frames of `body`
+    pub fn new(body: B) -> Self {
+        Self {
+            body,
+            trailers: None,
+        }
+    }
+
+    /// Get the trailers received so far, if any
+    pub fn trailers(&self) -> Option<&HeaderMap> {
+        self.trailers.as_ref()
+    }
+
+    /// Get a reference to the body
+    pub fn get_ref(&self) -> &B {
+        &self.body
+    }
+}
+
+impl<B> Stream for BodyStream<B>
+where
+    B: Body,
+{
+    type Item = Result<Bytes, B::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        loop {
+            let frame = match ready!(this.body.as_mut().poll_frame(cx)) {
+                Some(Ok(frame)) => frame,
+                Some(Err(err)) => return Poll::Ready(Some(Err(err))),
+                None => return Poll::Ready(None),
+            };
+            match frame.into_data() {
+                Ok(mut data) => {
+                    if data.has_remaining() {
+                        return Poll::Ready(Some(Ok(data.copy_to_bytes(data.remaining()))));
+                    }
+                }
+                Err(frame) => {
+                    if let Ok(trailers) = frame.into_trailers() {
+                        match this.trailers {
+                            Some(existing) => existing.extend(trailers),
+                            None => *this.trailers = Some(trailers),
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<B> EventStream<BodyStream<B>> {
+    /// Parse the events of an [`http_body::Body`], such as a `hyper::body::Incoming`
+    pub fn from_body(body: B) -> Self {
+        EventStream::new(BodyStream::new(body))
+    }
+}
+
+/// A `text/event-stream` [`http_body::Body`] encoding a Stream of events
+///
+/// The body fails with an [`EncodeError`] on an event which cannot be encoded.
+#[pin_project]
+pub struct EventBody<S> {
+    #[pin]
+    events: S,
+    buffer: String,
+}
+
+impl<S> EventBody<S> {
+    /// Encode every event of `events`
+    pub fn new(events: S) -> Self {
+        Self {
+            events,
+            buffer: String::new(),
+        }
+    }
+
+    /// Build a `200 OK` response with this body and the `Content-Type: text/event-stream` and
+    /// `Cache-Control: no-cache` headers
+    pub fn into_response(self) -> Response<Self> {
+        sse_response(self)
+    }
+}
+
+pub(crate) fn sse_response<B>(body: B) -> Response<B> {
+    let mut response = Response::new(body);
+    let headers = response.headers_mut();
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("text/event-stream"));
+    headers.insert(CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+    response
+}
+
+impl<S> Body for EventBody<S>
+where
+    S: Stream<Item = Event>,
+{
+    type Data = Bytes;
+    type Error = EncodeError;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.project();
+        let event = match ready!(this.events.poll_next(cx)) {
+            Some(event) => event,
+            None => return Poll::Ready(None),
+        };
+        let buffer = this.buffer;
+        buffer.clear();
+        Poll::Ready(Some(
+            encode_event(&event, buffer)
+                .map(|()| Frame::data(Bytes::copy_from_slice(buffer.as_bytes()))),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::prelude::*;
+    use http_body_util::{BodyExt, StreamBody};
+    use std::convert::Infallible;
+
+    #[tokio::test]
+    async fn body_stream() {
+        let mut trailers = HeaderMap::new();
+        trailers.insert("x-checksum", HeaderValue::from_static("abc"));
+        let frames = vec![
+            Ok::<_, Infallible>(Frame::data(Bytes::from_static(b"data: a\n"))),
+            Ok(Frame::data(Bytes::new())),
+            Ok(Frame::data(Bytes::from_static(b"\ndata: b\n\n"))),
+            Ok(Frame::trailers(trailers)),
+        ];
+        let mut stream = EventStream::from_body(StreamBody::new(stream::iter(frames)));
+        let mut events = Vec::new();
+        while let Some(event) = stream.try_next().await.unwrap() {
+            events.push(event.data);
+        }
+        assert_eq!(events, vec!["a", "b"]);
+        assert_eq!(
+            stream
+                .get_ref()
+                .trailers()
+                .unwrap()
+                .get("x-checksum")
+                .unwrap(),
+            "abc"
+        );
+    }
+
+    #[tokio::test]
+    async fn event_body() {
+        let events = vec![
+            Event {
+                event: "add".to_string(),
+                data: "1\n2".to_string(),
+                id: "7".to_string(),
+                retry: None,
+            },
+            Event {
+                data: "3".to_string(),
+                ..Default::default()
+            },
+        ];
+        let response = EventBody::new(stream::iter(events.clone())).into_response();
+        assert_eq!(response.headers()[CONTENT_TYPE], "text/event-stream");
+        assert_eq!(response.headers()[CACHE_CONTROL], "no-cache");
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(
+            &body[..],
+            b"event: add\nid: 7\ndata: 1\ndata: 2\n\ndata: 3\n\n"
+        );
+
+        let parsed = EventStream::from_body(EventBody::new(stream::iter(events)))
+            .map_ok(|event| event.data)
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(parsed, vec!["1\n2", "3"]);
+
+        let invalid = Event {
+            event: "a\nb".to_string(),
+            ..Default::default()
+        };
+        let result = EventBody::new(stream::iter(vec![invalid])).collect().await;
+        assert_eq!(result.err(), Some(EncodeError::EventLineBreak));
+    }
+}
@@ -0,0 +1,449 @@
+use crate::event_stream::EventStreamError;
+use crate::limits::{Limit, LimitAction, Limits};
+use crate::push_parser::StreamEnd;
+use core::convert::Infallible;
+use core::time::Duration;
+
+const BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// A line of the input without its terminator. Field values are slices of the input, a `&str`
+/// for [`crate::Parser`] and `Bytes` for `BytesParser`.
+pub(crate) enum Line<V> {
+    /// A comment without the leading colon
+    Comment(V),
+    /// A field name and value, which is empty if the line has no colon
+    Field(V, V),
+    Empty,
+}
+
+/// What parsing a line resulted in, besides building the event
+#[derive(Debug)]
+pub(crate) enum Parsed<V> {
+    Comment(V),
+    /// A field which is not part of the spec
+    Field(V, V),
+    /// A valid `retry` field changed the reconnection time
+    Retry(Duration),
+    /// A blank line changed the last event ID without dispatching an event
+    Id,
+    /// An event was dispatched into [`ParserCore::dispatched`]
+    Event,
+}
+
+impl<V> Parsed<V> {
+    pub(crate) fn map<W>(self, f: impl Fn(V) -> W) -> Parsed<W> {
+        match self {
+            Self::Comment(comment) => Parsed::Comment(f(comment)),
+            Self::Field(field, value) => Parsed::Field(f(field), f(value)),
+            Self::Retry(retry) => Parsed::Retry(retry),
+            Self::Id => Parsed::Id,
+            Self::Event => Parsed::Event,
+        }
+    }
+}
+
+/// Storage of the event being built and of the last event ID
+pub(crate) trait EventBuffer {
+    /// The dispatched event
+    type Event: Default;
+
+    /// Whether a `data` field was added
+    fn has_data(&self) -> bool;
+    /// Size of the data buffer in bytes, counting a line feed per `data` field
+    fn data_len(&self) -> usize;
+    fn set_retry(&mut self, retry: Duration);
+    /// Set the last event ID to the id of the event being built, returning whether it changed
+    fn commit_id(&mut self) -> bool;
+    /// Drop the event being built, starting the next one with the last event ID
+    fn clear(&mut self);
+    /// Move the event being built into `out`, without the line feed after its last data line
+    fn take_into(&mut self, out: &mut Self::Event);
+}
+
+/// Setting the fields of an [`EventBuffer`] from slices of the input
+pub(crate) trait SetField<V>: EventBuffer {
+    fn set_event(&mut self, value: V);
+    /// Append the value and a line feed to the data buffer
+    fn push_data(&mut self, value: V);
+    fn set_id(&mut self, value: V);
+}
+
+/// The input buffered by a parser which was not split into lines yet
+pub(crate) trait Input {
+    /// Whether there is no incomplete line at the end of the input
+    fn is_empty(&self) -> bool;
+    /// Length of the line at the end of the input whose terminator was not received yet. A line
+    /// ending in CR counts as complete.
+    fn partial_len(&self) -> usize;
+    /// Number of bytes received which were not parsed yet, including the incomplete line
+    fn buffered_len(&self) -> usize;
+    /// Whether the input up to the next line terminator is dropped, see [`Input::discard_line`]
+    fn is_discarding(&self) -> bool;
+    fn clear(&mut self);
+    /// Drop the incomplete line at the end of the input along with everything received up to
+    /// its line terminator. The line is replaced by an empty comment so the terminator still
+    /// ends a line instead of being read as a blank line.
+    fn discard_line(&mut self);
+    /// Terminate the incomplete line at the end of the input
+    fn end_line(&mut self);
+}
+
+#[derive(Default, Debug)]
+pub(crate) struct EventBuilder<B> {
+    pub(crate) buffer: B,
+    is_pending: bool,
+    is_skipping: bool,
+    fields: usize,
+    /// The reconnection time last set by a `retry` field
+    pub(crate) retry: Option<Duration>,
+}
+
+impl<B: EventBuffer> EventBuilder<B> {
+    /// From the HTML spec
+    ///
+    /// -> If the field name is "event"
+    ///    Set the event type buffer to field value.
+    ///
+    /// -> If the field name is "data"
+    ///    Append the field value to the data buffer, then append a single U+000A LINE FEED (LF)
+    ///    character to the data buffer.
+    ///
+    /// -> If the field name is "id"
+    ///    If the field value does not contain U+0000 NULL, then set the last event ID buffer
+    ///    to the field value. Otherwise, ignore the field.
+    ///
+    /// -> If the field name is "retry"
+    ///    If the field value consists of only ASCII digits, then interpret the field value as
+    ///    an integer in base ten, and set the event stream's reconnection time to that integer.
+    ///    Otherwise, ignore the field.
+    ///
+    /// -> Otherwise
+    ///    The field is ignored.
+    ///
+    /// Fields which are not part of the spec and `retry` fields changing the reconnection time
+    /// are returned for the extended mode of [`crate::Parser`].
+    fn add<V>(&mut self, field: V, value: V) -> Option<Parsed<V>>
+    where
+        B: SetField<V>,
+        V: AsRef<[u8]>,
+    {
+        self.is_pending = true;
+        self.fields += 1;
+        match field.as_ref() {
+            b"event" => self.buffer.set_event(value),
+            b"data" => self.buffer.push_data(value),
+            b"id" if !value.as_ref().contains(&0) => self.buffer.set_id(value),
+            b"id" => {}
+            b"retry" => {
+                let retry = parse_retry(value.as_ref())?;
+                self.buffer.set_retry(retry);
+                if self.retry.replace(retry) != Some(retry) {
+                    return Some(Parsed::Retry(retry));
+                }
+            }
+            _ => return Some(Parsed::Field(field, value)),
+        }
+        None
+    }
+
+    /// From the HTML spec
+    ///
+    /// 1. Set the last event ID string of the event source to the value of the last event ID
+    ///    buffer. The buffer does not get reset, so the last event ID string of the event source
+    ///    remains set to this value until the next time it is set by the server.
+    /// 2. If the data buffer is an empty string, set the data buffer and the event type buffer
+    ///    to the empty string and return.
+    /// 3. If the data buffer's last character is a U+000A LINE FEED (LF) character, then remove
+    ///    the last character from the data buffer.
+    /// 4. Let event be the result of creating an event using MessageEvent, in the relevant Realm
+    ///    of the EventSource object.
+    /// 5. Initialize event's type attribute to message, its data attribute to data, its origin
+    ///    attribute to the serialization of the origin of the event stream's final URL (i.e., the
+    ///    URL after redirects), and its lastEventId attribute to the last event ID string of the
+    ///    event source.
+    /// 6. If the event type buffer has a value other than the empty string, change the type of
+    ///    the newly created event to equal the value of the event type buffer.
+    /// 7. Set the data buffer and the event type buffer to the empty string.
+    /// 8. Queue a task which, if the readyState attribute is set to a value other than CLOSED,
+    ///    dispatches the newly created event at the EventSource object.
+    ///
+    /// The event is moved into `out`. Returns whether an event was dispatched, or else whether
+    /// the last event ID changed.
+    fn dispatch_into<V>(&mut self, out: &mut B::Event) -> Option<Parsed<V>> {
+        let is_skipping = core::mem::take(&mut self.is_skipping);
+        self.is_pending = false;
+        self.fields = 0;
+        let is_id_changed = self.buffer.commit_id();
+        let parsed = if !is_skipping && self.buffer.has_data() {
+            self.buffer.take_into(out);
+            Some(Parsed::Event)
+        } else if is_id_changed {
+            Some(Parsed::Id)
+        } else {
+            None
+        };
+        self.buffer.clear();
+        parsed
+    }
+
+    /// Drop the event being built and ignore all fields until the next blank line
+    fn skip(&mut self) {
+        self.buffer.clear();
+        self.fields = 0;
+        self.is_skipping = true;
+    }
+
+    fn check_limits(&self, limits: &Limits) -> Option<Limit> {
+        if Limits::exceeds(limits.max_fields, self.fields) {
+            Some(Limit::Fields)
+        } else if Limits::exceeds(limits.max_event_size, self.buffer.data_len()) {
+            Some(Limit::EventSize)
+        } else {
+            None
+        }
+    }
+}
+
+/// Only ASCII digits are a valid reconnection time
+fn parse_retry(value: &[u8]) -> Option<Duration> {
+    if value.is_empty() || !value.iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+    let millis = core::str::from_utf8(value).ok()?.parse::<u64>().ok()?;
+    Some(Duration::from_millis(millis))
+}
+
+/// Strips a byte order mark from the start of the input, which may be split across chunks
+#[derive(Default, Debug)]
+pub(crate) struct Bom {
+    /// Length of the byte order mark prefix received before the first other byte
+    held: usize,
+    is_started: bool,
+}
+
+impl Bom {
+    /// Returns the bytes held back so far if they turned out not to start a byte order mark, and
+    /// the number of leading bytes of `chunk` which are part of one
+    pub(crate) fn strip(&mut self, chunk: &[u8]) -> (&'static [u8], usize) {
+        if self.is_started {
+            return (&[], 0);
+        }
+        let held = self.held;
+        let len = (BOM.len() - held).min(chunk.len());
+        if chunk[..len] == BOM[held..held + len] {
+            self.held += len;
+            self.is_started = self.held == BOM.len();
+            return (&[], len);
+        }
+        self.is_started = true;
+        (&BOM[..held], 0)
+    }
+
+    /// Returns the bytes held back at the end of the input
+    pub(crate) fn finish(&mut self) -> &'static [u8] {
+        if self.is_started {
+            return &[];
+        }
+        self.is_started = true;
+        &BOM[..self.held]
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum State {
+    Parsing,
+    /// The input ended, but the buffered lines were not parsed yet
+    Finished,
+    Terminated(StreamEnd),
+}
+
+/// What a parser does once all complete lines of its input were parsed
+pub(crate) enum EndOfLines {
+    /// Wait for more input
+    Pending,
+    /// Parse the line terminated by [`Input::end_line`]
+    Flush,
+    /// An event was dispatched into [`ParserCore::dispatched`]
+    Event,
+}
+
+/// Everything of a parser but its input: the event being built, the limits and how the input
+/// ended. [`crate::Parser`] and `BytesParser` only differ in how they split their input into
+/// lines and store the event.
+#[derive(Debug)]
+pub(crate) struct ParserCore<B: EventBuffer> {
+    pub(crate) builder: EventBuilder<B>,
+    /// The last dispatched event
+    pub(crate) dispatched: B::Event,
+    pub(crate) bom: Bom,
+    state: State,
+    pub(crate) flush_on_eof: bool,
+    is_flushing: bool,
+    pub(crate) limits: Limits,
+}
+
+impl<B: EventBuffer + Default> Default for ParserCore<B> {
+    fn default() -> Self {
+        Self {
+            builder: EventBuilder::default(),
+            dispatched: B::Event::default(),
+            bom: Bom::default(),
+            state: State::Parsing,
+            flush_on_eof: false,
+            is_flushing: false,
+            limits: Limits::default(),
+        }
+    }
+}
+
+impl<B: EventBuffer> ParserCore<B> {
+    /// Whether the parser takes no more input, because it was finished or failed on a limit
+    pub(crate) fn is_terminated(&self) -> bool {
+        !matches!(self.state, State::Parsing)
+    }
+
+    pub(crate) fn stream_end(&self) -> Option<StreamEnd> {
+        match self.state {
+            State::Terminated(end) => Some(end),
+            _ => None,
+        }
+    }
+
+    /// Mark the end of the input
+    pub(crate) fn finish(&mut self) {
+        if !self.is_terminated() {
+            self.state = State::Finished;
+        }
+    }
+
+    /// Build the event from a complete line which is `len` bytes long
+    pub(crate) fn parse_line<V>(
+        &mut self,
+        line: Line<V>,
+        len: usize,
+    ) -> Result<Option<Parsed<V>>, Limit>
+    where
+        B: SetField<V>,
+        V: AsRef<[u8]>,
+    {
+        if self.builder.is_skipping {
+            if let Line::Empty = line {
+                return Ok(self.builder.dispatch_into(&mut self.dispatched));
+            }
+            return Ok(None);
+        }
+        if Limits::exceeds(self.limits.max_line_length, len) {
+            return Err(Limit::LineLength);
+        }
+        match line {
+            Line::Comment(comment) => Ok(Some(Parsed::Comment(comment))),
+            Line::Field(field, value) => {
+                let parsed = self.builder.add(field, value);
+                match self.builder.check_limits(&self.limits) {
+                    Some(limit) => Err(limit),
+                    None => Ok(parsed),
+                }
+            }
+            Line::Empty => Ok(self.builder.dispatch_into(&mut self.dispatched)),
+        }
+    }
+
+    /// Check the bytes held from earlier chunks against `max_buffered` before a chunk is added.
+    /// The chunk is refused instead of dropping the events which were not parsed yet. While a
+    /// line is discarded the input does not grow, and the empty comment left in its place must
+    /// not refuse the chunk holding its terminator.
+    pub(crate) fn check_buffered(
+        &self,
+        input: &impl Input,
+    ) -> Result<(), EventStreamError<Infallible>> {
+        if !input.is_discarding() && Limits::exceeds(self.limits.max_buffered, input.buffered_len())
+        {
+            return Err(EventStreamError::LimitExceeded(Limit::Buffered));
+        }
+        Ok(())
+    }
+
+    /// Check the length of the incomplete line at the end of the input against
+    /// `max_line_length`, and the input left against `max_buffered`. Only called once the
+    /// complete lines before it were parsed, so exceeding a limit never drops the events they
+    /// hold.
+    pub(crate) fn check_partial(
+        &mut self,
+        input: &mut impl Input,
+    ) -> Result<(), EventStreamError<Infallible>> {
+        let limit = if Limits::exceeds(self.limits.max_line_length, input.partial_len()) {
+            Limit::LineLength
+        } else if Limits::exceeds(self.limits.max_buffered, input.buffered_len()) {
+            Limit::Buffered
+        } else {
+            return Ok(());
+        };
+        if self.builder.is_skipping {
+            input.discard_line();
+            return Ok(());
+        }
+        Err(self.limit_exceeded(limit, input, true))
+    }
+
+    /// Drop the event being built, and either terminate or also drop the incomplete line at the
+    /// end of the input if `discard_line` is set
+    pub(crate) fn limit_exceeded(
+        &mut self,
+        limit: Limit,
+        input: &mut impl Input,
+        discard_line: bool,
+    ) -> EventStreamError<Infallible> {
+        self.builder.skip();
+        match self.limits.on_exceeded {
+            LimitAction::Fail => {
+                input.clear();
+                self.is_flushing = false;
+                self.state = State::Terminated(StreamEnd::Aborted);
+            }
+            LimitAction::Skip if discard_line => input.discard_line(),
+            LimitAction::Skip => {}
+        }
+        EventStreamError::LimitExceeded(limit)
+    }
+
+    /// End the input at invalid bytes. The complete lines before them are still parsed, but the
+    /// incomplete line is dropped along with the event left partly built.
+    pub(crate) fn fail_input(&mut self, input: &mut impl Input) {
+        input.discard_line();
+        self.is_flushing = false;
+        self.state = State::Terminated(StreamEnd::Aborted);
+    }
+
+    /// Terminate once the complete lines buffered at the end of the input were parsed, then
+    /// dispatch the partly built event if enabled by `flush_on_eof`
+    pub(crate) fn end_of_lines(&mut self, input: &mut impl Input) -> EndOfLines {
+        match self.state {
+            State::Finished => {
+                let end = if input.is_empty() && !self.builder.is_pending {
+                    StreamEnd::Clean
+                } else {
+                    StreamEnd::MidEvent
+                };
+                self.state = State::Terminated(end);
+                if !self.flush_on_eof {
+                    return EndOfLines::Pending;
+                }
+                if !input.is_empty() {
+                    input.end_line();
+                }
+                self.is_flushing = true;
+                EndOfLines::Flush
+            }
+            _ if self.is_flushing => {
+                self.is_flushing = false;
+                if let Some(Parsed::Event) = self.builder.dispatch_into::<()>(&mut self.dispatched)
+                {
+                    EndOfLines::Event
+                } else {
+                    EndOfLines::Pending
+                }
+            }
+            _ => EndOfLines::Pending,
+        }
+    }
+}
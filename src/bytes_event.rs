@@ -0,0 +1,691 @@
+use crate::builder::{EndOfLines, EventBuffer, Input, Line, Parsed, ParserCore, SetField};
+use crate::event_stream::EventStreamError;
+use crate::limits::Limits;
+use crate::push_parser::StreamEnd;
+use bytes::{Buf, Bytes, BytesMut};
+use core::convert::Infallible;
+use core::pin::Pin;
+use core::str::Utf8Error;
+use core::time::Duration;
+use futures_core::ready;
+use futures_core::stream::Stream;
+use futures_core::task::{Context, Poll};
+use pin_project::pin_project;
+use std::collections::VecDeque;
+
+/// An event whose fields are slices of the received chunks of bytes, see [`BytesParser`]
+///
+/// The fields are not validated as UTF-8, use [`BytesEvent::data_str`] and friends to read them
+/// as text.
+#[derive(Default, Debug, Clone, Eq, PartialEq)]
+pub struct BytesEvent {
+    /// The event name if given
+    pub event: Bytes,
+    /// The event data
+    pub data: Bytes,
+    /// The event id if given
+    pub id: Bytes,
+    /// Retry duration if given
+    pub retry: Option<Duration>,
+}
+
+impl BytesEvent {
+    /// Get the event name as text
+    pub fn event_str(&self) -> Result<&str, Utf8Error> {
+        core::str::from_utf8(&self.event)
+    }
+
+    /// Get the event data as text
+    pub fn data_str(&self) -> Result<&str, Utf8Error> {
+        core::str::from_utf8(&self.data)
+    }
+
+    /// Get the event id as text
+    pub fn id_str(&self) -> Result<&str, Utf8Error> {
+        core::str::from_utf8(&self.id)
+    }
+}
+
+#[inline]
+fn is_eol(b: &u8) -> bool {
+    *b == b'\n' || *b == b'\r'
+}
+
+/// The event being built by a [`BytesParser`]. Data lines are only copied when they have to be
+/// joined.
+#[derive(Default, Debug)]
+struct BytesFields {
+    event: BytesEvent,
+    /// The joined data lines, only used once an event has more than one
+    data: BytesMut,
+    data_lines: usize,
+    data_size: usize,
+    last_event_id: Bytes,
+}
+
+impl EventBuffer for BytesFields {
+    type Event = BytesEvent;
+
+    fn has_data(&self) -> bool {
+        self.data_lines > 0
+    }
+
+    fn data_len(&self) -> usize {
+        self.data_size
+    }
+
+    fn set_retry(&mut self, retry: Duration) {
+        self.event.retry = Some(retry);
+    }
+
+    fn commit_id(&mut self) -> bool {
+        if self.last_event_id == self.event.id {
+            return false;
+        }
+        self.last_event_id = self.event.id.clone();
+        true
+    }
+
+    fn clear(&mut self) {
+        self.event = BytesEvent {
+            id: self.last_event_id.clone(),
+            ..Default::default()
+        };
+        self.data.clear();
+        self.data_lines = 0;
+        self.data_size = 0;
+    }
+
+    fn take_into(&mut self, out: &mut BytesEvent) {
+        *out = core::mem::take(&mut self.event);
+        if self.data_lines > 1 {
+            out.data = self.data.split().freeze();
+        }
+    }
+}
+
+impl SetField<Bytes> for BytesFields {
+    fn set_event(&mut self, value: Bytes) {
+        self.event.event = value;
+    }
+
+    fn push_data(&mut self, value: Bytes) {
+        self.data_size += value.len() + 1;
+        self.data_lines += 1;
+        match self.data_lines {
+            1 => self.event.data = value,
+            2 => {
+                self.data.clear();
+                self.data.extend_from_slice(&self.event.data);
+                self.data.extend_from_slice(b"\n");
+                self.data.extend_from_slice(&value);
+            }
+            _ => {
+                self.data.extend_from_slice(b"\n");
+                self.data.extend_from_slice(&value);
+            }
+        }
+    }
+
+    fn set_id(&mut self, value: Bytes) {
+        self.event.id = value;
+    }
+}
+
+/// Split a line without its terminator into a field name and value, slicing the line
+fn split_line(line: Bytes) -> Line<Bytes> {
+    if line.is_empty() {
+        return Line::Empty;
+    }
+    if line[0] == b':' {
+        return Line::Comment(line.slice(1..));
+    }
+    match line.iter().position(|b| *b == b':') {
+        Some(pos) => {
+            let mut value = line.slice(pos + 1..);
+            if value.first() == Some(&b' ') {
+                value.advance(1);
+            }
+            Line::Field(line.slice(..pos), value)
+        }
+        None => Line::Field(line, Bytes::new()),
+    }
+}
+
+/// Chunks received from the source which were not split into lines yet
+#[derive(Debug, Default)]
+struct Chunks {
+    chunks: VecDeque<Bytes>,
+    /// Start of a line whose end was not received yet
+    partial: BytesMut,
+    /// Length of the line whose terminator was not received yet
+    partial_len: usize,
+    skip_lf: bool,
+    is_discarding: bool,
+}
+
+impl Chunks {
+    fn push(&mut self, mut chunk: Bytes) {
+        if self.is_discarding {
+            match chunk.iter().position(is_eol) {
+                Some(pos) => {
+                    self.is_discarding = false;
+                    chunk.advance(pos);
+                }
+                None => return,
+            }
+        }
+        match chunk.iter().rposition(is_eol) {
+            Some(pos) => self.partial_len = chunk.len() - pos - 1,
+            None => self.partial_len += chunk.len(),
+        }
+        if !chunk.is_empty() {
+            self.chunks.push_back(chunk);
+        }
+    }
+
+    /// Take the next complete line without its terminator, or `None` if more input is needed
+    fn next_line(&mut self) -> Option<Bytes> {
+        loop {
+            let chunk = self.chunks.front_mut()?;
+            if chunk.is_empty() {
+                self.chunks.pop_front();
+                continue;
+            }
+            if self.skip_lf {
+                self.skip_lf = false;
+                if chunk[0] == b'\n' {
+                    chunk.advance(1);
+                    continue;
+                }
+            }
+            match chunk.iter().position(is_eol) {
+                Some(pos) => {
+                    self.skip_lf = chunk[pos] == b'\r';
+                    let line = chunk.split_to(pos);
+                    chunk.advance(1);
+                    if self.partial.is_empty() {
+                        return Some(line);
+                    }
+                    self.partial.extend_from_slice(&line);
+                    return Some(self.partial.split().freeze());
+                }
+                None => {
+                    self.partial.extend_from_slice(chunk);
+                    self.chunks.pop_front();
+                }
+            }
+        }
+    }
+}
+
+impl Input for Chunks {
+    fn is_empty(&self) -> bool {
+        self.partial.is_empty() && self.chunks.iter().all(Bytes::is_empty)
+    }
+
+    fn partial_len(&self) -> usize {
+        self.partial_len
+    }
+
+    fn buffered_len(&self) -> usize {
+        self.partial.len() + self.chunks.iter().map(Bytes::len).sum::<usize>()
+    }
+
+    fn is_discarding(&self) -> bool {
+        self.is_discarding
+    }
+
+    fn clear(&mut self) {
+        self.chunks.clear();
+        self.partial.clear();
+        self.partial_len = 0;
+        self.is_discarding = false;
+    }
+
+    fn discard_line(&mut self) {
+        while let Some(chunk) = self.chunks.back_mut() {
+            if let Some(pos) = chunk.iter().rposition(is_eol) {
+                chunk.truncate(pos + 1);
+                break;
+            }
+            self.chunks.pop_back();
+        }
+        if self.chunks.is_empty() {
+            self.partial.clear();
+        }
+        self.chunks.push_back(Bytes::from_static(b":"));
+        self.partial_len = 1;
+        self.is_discarding = true;
+    }
+
+    fn end_line(&mut self) {
+        self.chunks.push_back(Bytes::from_static(b"\n"));
+        self.partial_len = 0;
+    }
+}
+
+/// A push parser like [`crate::Parser`] which yields [`BytesEvent`]s instead of copying every
+/// field into a `String`
+///
+/// Lines which lie within one chunk are sliced out of it without copying, so single line data
+/// stays zero-copy. Only lines split across chunks and events with several `data` lines are
+/// copied. The input is not decoded, so there is no UTF-8 policy.
+#[derive(Debug, Default)]
+pub struct BytesParser {
+    chunks: Chunks,
+    core: ParserCore<BytesFields>,
+}
+
+impl BytesParser {
+    /// Create a parser with the default settings
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start from a known last event ID, see [`crate::EventStream::with_last_event_id`]
+    pub fn with_last_event_id(mut self, last_event_id: impl Into<Bytes>) -> Self {
+        let fields = &mut self.core.builder.buffer;
+        fields.last_event_id = last_event_id.into();
+        fields.event.id = fields.last_event_id.clone();
+        self
+    }
+
+    /// Start from a known reconnection time, see [`crate::EventStream::with_retry`]
+    pub fn with_retry(mut self, retry: Duration) -> Self {
+        self.core.builder.retry = Some(retry);
+        self
+    }
+
+    /// Dispatch a partly built event when the parser is finished, see
+    /// [`crate::EventStream::with_flush_on_eof`]
+    pub fn with_flush_on_eof(mut self, flush_on_eof: bool) -> Self {
+        self.core.flush_on_eof = flush_on_eof;
+        self
+    }
+
+    /// Set the size limits applied to lines, events and buffered data
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.core.limits = limits;
+        self
+    }
+
+    /// Get the last event ID, see [`crate::Parser::last_event_id`]
+    pub fn last_event_id(&self) -> &Bytes {
+        &self.core.builder.buffer.last_event_id
+    }
+
+    /// Get the reconnection time last set by a `retry` field
+    pub fn retry(&self) -> Option<Duration> {
+        self.core.builder.retry
+    }
+
+    /// Get how the input ended, or `None` until all input was parsed after
+    /// [`BytesParser::finish`]
+    pub fn stream_end(&self) -> Option<StreamEnd> {
+        self.core.stream_end()
+    }
+
+    /// Pass the next chunk of bytes to the parser. Input fed after the parser was finished is
+    /// ignored.
+    ///
+    /// The chunk is refused like by [`crate::Parser::feed`] if more than
+    /// [`Limits::max_buffered`] bytes fed earlier were not parsed yet. An incomplete line
+    /// exceeding the limit is reported by [`BytesParser::next_event`], after the events before
+    /// it.
+    pub fn feed(&mut self, chunk: impl Into<Bytes>) -> Result<(), EventStreamError<Infallible>> {
+        if self.core.is_terminated() {
+            return Ok(());
+        }
+        self.core.check_buffered(&self.chunks)?;
+        self.push(chunk.into());
+        Ok(())
+    }
+
+    /// Pass the next chunk without checking [`Limits::max_buffered`], see
+    /// [`crate::Parser::push`]
+    fn push(&mut self, mut chunk: Bytes) {
+        if self.core.is_terminated() {
+            return;
+        }
+        let (held, bom_len) = self.core.bom.strip(&chunk);
+        if !held.is_empty() {
+            self.chunks.push(Bytes::from_static(held));
+        }
+        chunk.advance(bom_len);
+        self.chunks.push(chunk);
+    }
+
+    /// Mark the end of the input, see [`crate::Parser::finish`]
+    pub fn finish(&mut self) {
+        if self.core.is_terminated() {
+            return;
+        }
+        let held = self.core.bom.finish();
+        self.chunks.push(Bytes::from_static(held));
+        self.core.finish();
+    }
+
+    /// Parse the next event from the input fed so far, or `None` if more input is needed
+    pub fn next_event(&mut self) -> Option<Result<BytesEvent, EventStreamError<Infallible>>> {
+        loop {
+            let line = match self.chunks.next_line() {
+                Some(line) => line,
+                None => {
+                    if let Err(err) = self.core.check_partial(&mut self.chunks) {
+                        return Some(Err(err));
+                    }
+                    match self.core.end_of_lines(&mut self.chunks) {
+                        EndOfLines::Pending => return None,
+                        EndOfLines::Flush => continue,
+                        EndOfLines::Event => break,
+                    }
+                }
+            };
+            let len = line.len();
+            match self.core.parse_line(split_line(line), len) {
+                Ok(Some(Parsed::Event)) => break,
+                Ok(_) => {}
+                Err(limit) => {
+                    return Some(Err(self.core.limit_exceeded(
+                        limit,
+                        &mut self.chunks,
+                        false,
+                    )))
+                }
+            }
+        }
+        Some(Ok(core::mem::take(&mut self.core.dispatched)))
+    }
+}
+
+/// A Stream of [`BytesEvent`]s parsed from a stream of byte chunks, such as
+/// `reqwest::Response::bytes_stream`
+#[pin_project]
+pub struct BytesEventStream<S> {
+    #[pin]
+    stream: S,
+    parser: BytesParser,
+}
+
+impl<S> BytesEventStream<S> {
+    /// Create an event stream from a stream of byte chunks
+    pub fn new(stream: S) -> Self {
+        Self {
+            stream,
+            parser: BytesParser::new(),
+        }
+    }
+
+    /// Start from a known last event ID, see [`crate::EventStream::with_last_event_id`]
+    pub fn with_last_event_id(mut self, last_event_id: impl Into<Bytes>) -> Self {
+        self.parser = self.parser.with_last_event_id(last_event_id);
+        self
+    }
+
+    /// Start from a known reconnection time, see [`crate::EventStream::with_retry`]
+    pub fn with_retry(mut self, retry: Duration) -> Self {
+        self.parser = self.parser.with_retry(retry);
+        self
+    }
+
+    /// Dispatch a partly built event at the end of the source, see
+    /// [`crate::EventStream::with_flush_on_eof`]
+    pub fn with_flush_on_eof(mut self, flush_on_eof: bool) -> Self {
+        self.parser = self.parser.with_flush_on_eof(flush_on_eof);
+        self
+    }
+
+    /// Set the size limits applied to lines, events and buffered data
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.parser = self.parser.with_limits(limits);
+        self
+    }
+
+    /// Get the last event ID of the stream
+    pub fn last_event_id(&self) -> &Bytes {
+        self.parser.last_event_id()
+    }
+
+    /// Get the reconnection time last set by a `retry` field
+    pub fn retry(&self) -> Option<Duration> {
+        self.parser.retry()
+    }
+
+    /// Get how the source stream ended, or `None` if it has not ended yet, see
+    /// [`BytesParser::stream_end`]
+    pub fn stream_end(&self) -> Option<StreamEnd> {
+        self.parser.stream_end()
+    }
+}
+
+impl<S, B, E> Stream for BytesEventStream<S>
+where
+    S: Stream<Item = Result<B, E>>,
+    B: Into<Bytes>,
+{
+    type Item = Result<BytesEvent, EventStreamError<E>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        loop {
+            if let Some(event) = this.parser.next_event() {
+                return Poll::Ready(Some(event.map_err(EventStreamError::into_transport)));
+            }
+            if this.parser.core.is_terminated() {
+                return Poll::Ready(None);
+            }
+            match ready!(this.stream.as_mut().poll_next(cx)) {
+                Some(Ok(bytes)) => this.parser.push(bytes.into()),
+                Some(Err(err)) => return Poll::Ready(Some(Err(EventStreamError::Transport(err)))),
+                None => this.parser.finish(),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::limits::{Limit, LimitAction};
+    use futures::prelude::*;
+
+    fn event(event: &'static str, data: &'static str, id: &'static str) -> BytesEvent {
+        BytesEvent {
+            event: Bytes::from_static(event.as_bytes()),
+            data: Bytes::from_static(data.as_bytes()),
+            id: Bytes::from_static(id.as_bytes()),
+            retry: None,
+        }
+    }
+
+    fn is_slice_of(slice: &Bytes, chunk: &Bytes) -> bool {
+        let range = chunk.as_ptr_range();
+        range.start <= slice.as_ptr() && slice.as_ptr() < range.end
+    }
+
+    #[test]
+    fn zero_copy() {
+        let chunk = Bytes::from_static(b"event: add\nid: 1\ndata: hello\n\ndata: a\ndata: b\n\n");
+        let mut parser = BytesParser::new();
+        parser.feed(chunk.clone()).unwrap();
+
+        let first = parser.next_event().unwrap().unwrap();
+        assert_eq!(first, event("add", "hello", "1"));
+        assert!(is_slice_of(&first.event, &chunk));
+        assert!(is_slice_of(&first.data, &chunk));
+        assert!(is_slice_of(&first.id, &chunk));
+
+        let second = parser.next_event().unwrap().unwrap();
+        assert_eq!(second, event("", "a\nb", "1"));
+        assert!(!is_slice_of(&second.data, &chunk));
+        assert!(parser.next_event().is_none());
+        assert_eq!(parser.last_event_id(), "1");
+
+        parser.feed(Bytes::from_static(b"id: 2\n\n")).unwrap();
+        assert!(parser.next_event().is_none());
+        assert_eq!(parser.last_event_id(), "2");
+    }
+
+    #[test]
+    fn chunk_boundaries() {
+        let input = b"\xEF\xBB\xBFdata: one\r\n\r\ndata:two\r\rretry: 10\n:comment\ndata\n\n";
+        let mut parser = BytesParser::new();
+        let mut events = Vec::new();
+        for byte in input.iter() {
+            parser.feed(vec![*byte]).unwrap();
+            events.extend(parser.next_event().map(Result::unwrap));
+        }
+        parser.finish();
+        assert!(parser.next_event().is_none());
+        assert_eq!(
+            events,
+            vec![
+                event("", "one", ""),
+                event("", "two", ""),
+                BytesEvent {
+                    retry: Some(Duration::from_millis(10)),
+                    ..event("", "", "")
+                }
+            ]
+        );
+        assert_eq!(parser.retry(), Some(Duration::from_millis(10)));
+        assert_eq!(parser.stream_end(), Some(StreamEnd::Clean));
+    }
+
+    #[test]
+    fn limits() {
+        let mut parser = BytesParser::new().with_limits(Limits {
+            max_line_length: Some(12),
+            on_exceeded: LimitAction::Skip,
+            ..Default::default()
+        });
+        parser.feed("id: 1\ndata: too").unwrap();
+        assert!(parser.next_event().is_none());
+        parser.feed(" long").unwrap();
+        assert_eq!(
+            parser.next_event(),
+            Some(Err(EventStreamError::LimitExceeded(Limit::LineLength)))
+        );
+        parser.feed(" still\ndata: b\n\ndata: c\n\n").unwrap();
+        assert_eq!(parser.next_event(), Some(Ok(event("", "c", ""))));
+
+        let mut parser = BytesParser::new().with_limits(Limits {
+            max_buffered: Some(4),
+            ..Default::default()
+        });
+        parser.feed("data: ok\n\ndata: a").unwrap();
+        assert_eq!(parser.next_event(), Some(Ok(event("", "ok", ""))));
+        assert_eq!(
+            parser.next_event(),
+            Some(Err(EventStreamError::LimitExceeded(Limit::Buffered)))
+        );
+        assert!(parser.next_event().is_none());
+        assert_eq!(parser.stream_end(), Some(StreamEnd::Aborted));
+
+        let mut parser = BytesParser::new().with_limits(Limits {
+            max_buffered: Some(1024),
+            ..Default::default()
+        });
+        parser.feed("data: x\n\n".repeat(1000)).unwrap();
+        assert_eq!(core::iter::from_fn(|| parser.next_event()).count(), 1000);
+        parser.feed(vec![b'a'; 1024]).unwrap();
+        assert!(parser.next_event().is_none());
+        parser.feed("a").unwrap();
+        assert_eq!(
+            parser.next_event(),
+            Some(Err(EventStreamError::LimitExceeded(Limit::Buffered)))
+        );
+
+        let mut parser = BytesParser::new().with_limits(Limits {
+            max_buffered: Some(16),
+            ..Default::default()
+        });
+        parser.feed("data: a\n\ndata: b\n\n").unwrap();
+        assert_eq!(
+            parser.feed("data: c\n\n"),
+            Err(EventStreamError::LimitExceeded(Limit::Buffered))
+        );
+        assert_eq!(parser.next_event(), Some(Ok(event("", "a", ""))));
+        parser.feed("data: c\n\n").unwrap();
+        assert_eq!(parser.next_event(), Some(Ok(event("", "b", ""))));
+        assert_eq!(parser.next_event(), Some(Ok(event("", "c", ""))));
+
+        let mut parser = BytesParser::new().with_limits(Limits {
+            max_buffered: Some(0),
+            on_exceeded: LimitAction::Skip,
+            ..Default::default()
+        });
+        parser.feed("data: a").unwrap();
+        assert_eq!(
+            parser.next_event(),
+            Some(Err(EventStreamError::LimitExceeded(Limit::Buffered)))
+        );
+        parser.feed("bc").unwrap();
+        assert!(parser.next_event().is_none());
+        parser.feed("\n\n").unwrap();
+        assert!(parser.next_event().is_none());
+        parser.feed("data: ok\n\n").unwrap();
+        assert_eq!(parser.next_event(), Some(Ok(event("", "ok", ""))));
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn same_as_parser(
+            input in "\u{feff}?(data|id|event|retry|[a-z0-9: ]|\r|\n){0,64}",
+            split in 0usize..64,
+            max_line_length in proptest::option::of(0usize..16),
+        ) {
+            let input = input.as_bytes();
+            let split = split.min(input.len());
+            let limits = Limits {
+                max_line_length,
+                on_exceeded: LimitAction::Skip,
+                ..Default::default()
+            };
+            let mut expected = Vec::new();
+            let mut parser = crate::Parser::new().with_flush_on_eof(true).with_limits(limits);
+            for chunk in [&input[..split], &input[split..]] {
+                parser.feed(chunk).unwrap();
+                while let Some(event) = parser.next_event() {
+                    expected.push(event.map(|event| (event.event, event.data, event.id, event.retry)));
+                }
+            }
+            parser.finish();
+            while let Some(event) = parser.next_event() {
+                expected.push(event.map(|event| (event.event, event.data, event.id, event.retry)));
+            }
+
+            let mut events = Vec::new();
+            let mut bytes_parser = BytesParser::new().with_flush_on_eof(true).with_limits(limits);
+            let text = |bytes: Bytes| String::from_utf8(bytes.to_vec()).unwrap();
+            for chunk in [&input[..split], &input[split..]] {
+                bytes_parser.feed(chunk.to_vec()).unwrap();
+                while let Some(event) = bytes_parser.next_event() {
+                    events.push(event.map(|event| (text(event.event), text(event.data), text(event.id), event.retry)));
+                }
+            }
+            bytes_parser.finish();
+            while let Some(event) = bytes_parser.next_event() {
+                events.push(event.map(|event| (text(event.event), text(event.data), text(event.id), event.retry)));
+            }
+            proptest::prop_assert_eq!(events, expected);
+            proptest::prop_assert_eq!(bytes_parser.stream_end(), parser.stream_end());
+            proptest::prop_assert_eq!(text(bytes_parser.last_event_id().clone()), parser.last_event_id());
+        }
+    }
+
+    #[tokio::test]
+    async fn stream() {
+        let mut stream = BytesEventStream::new(stream::iter(vec![
+            Ok::<_, ()>("data: a\n\nid: 2\n"),
+            Ok("data: b"),
+        ]))
+        .with_flush_on_eof(true);
+        assert_eq!(
+            stream.by_ref().try_collect::<Vec<_>>().await.unwrap(),
+            vec![event("", "a", ""), event("", "b", "2")]
+        );
+        assert_eq!(stream.stream_end(), Some(StreamEnd::MidEvent));
+    }
+}
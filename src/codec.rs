@@ -0,0 +1,220 @@
+use crate::encoder::{encode_event, EncodeError};
+use crate::event::Event;
+use crate::event_stream::EventStreamError;
+use crate::limits::Limits;
+use crate::push_parser::Parser;
+use crate::utf8::Utf8Policy;
+use bytes::BytesMut;
+use core::convert::Infallible;
+use core::fmt;
+use core::time::Duration;
+use std::io;
+use tokio_util::codec::{Decoder, Encoder};
+
+/// A [`Decoder`] and [`Encoder`] of events in `text/event-stream` format, to be used with
+/// `FramedRead` and `FramedWrite`
+///
+/// Decoding goes through the same [`Parser`] as an [`crate::EventStream`], so limits, the UTF-8
+/// policy and byte order mark handling behave the same.
+#[derive(Debug, Default)]
+pub struct SseCodec {
+    parser: Parser,
+}
+
+impl SseCodec {
+    /// Create a codec with the default settings
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start from a known last event ID, see [`crate::EventStream::with_last_event_id`]
+    pub fn with_last_event_id(mut self, last_event_id: impl Into<String>) -> Self {
+        self.parser = self.parser.with_last_event_id(last_event_id);
+        self
+    }
+
+    /// Start from a known reconnection time, see [`crate::EventStream::with_retry`]
+    pub fn with_retry(mut self, retry: Duration) -> Self {
+        self.parser = self.parser.with_retry(retry);
+        self
+    }
+
+    /// Dispatch a partly built event at the end of the input, see
+    /// [`crate::EventStream::with_flush_on_eof`]
+    pub fn with_flush_on_eof(mut self, flush_on_eof: bool) -> Self {
+        self.parser = self.parser.with_flush_on_eof(flush_on_eof);
+        self
+    }
+
+    /// Set how invalid UTF-8 in the input is handled. Defaults to [`Utf8Policy::Lossy`].
+    pub fn with_utf8_policy(mut self, policy: Utf8Policy) -> Self {
+        self.parser = self.parser.with_utf8_policy(policy);
+        self
+    }
+
+    /// Set the size limits applied to lines, events and buffered data
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.parser = self.parser.with_limits(limits);
+        self
+    }
+
+    /// Get the last event ID of the decoded input
+    pub fn last_event_id(&self) -> &str {
+        self.parser.last_event_id()
+    }
+
+    /// Get the reconnection time last set by a `retry` field
+    pub fn retry(&self) -> Option<Duration> {
+        self.parser.retry()
+    }
+}
+
+/// Error returned by an [`SseCodec`]
+#[derive(Debug)]
+pub enum SseCodecError {
+    /// The input is not a valid event stream or exceeds a limit
+    Decode(EventStreamError<Infallible>),
+    /// The event cannot be encoded
+    Encode(EncodeError),
+    /// Underlying reader or writer error
+    Io(io::Error),
+}
+
+impl From<EventStreamError<Infallible>> for SseCodecError {
+    fn from(err: EventStreamError<Infallible>) -> Self {
+        Self::Decode(err)
+    }
+}
+
+impl From<EncodeError> for SseCodecError {
+    fn from(err: EncodeError) -> Self {
+        Self::Encode(err)
+    }
+}
+
+impl From<io::Error> for SseCodecError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl fmt::Display for SseCodecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Decode(err) => err.fmt(f),
+            Self::Encode(err) => f.write_fmt(format_args!("Encode error: {}", err)),
+            Self::Io(err) => f.write_fmt(format_args!("IO error: {}", err)),
+        }
+    }
+}
+
+impl std::error::Error for SseCodecError {}
+
+impl Decoder for SseCodec {
+    type Item = Event;
+    type Error = SseCodecError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Event>, Self::Error> {
+        // The events parsed so far are pulled before more input is passed on
+        if let Some(event) = self.parser.next_event() {
+            return Ok(Some(event?));
+        }
+        if !src.is_empty() {
+            self.parser.push(src);
+            src.clear();
+        }
+        Ok(self.parser.next_event().transpose()?)
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<Event>, Self::Error> {
+        if let Some(event) = self.decode(src)? {
+            return Ok(Some(event));
+        }
+        self.parser.finish();
+        Ok(self.parser.next_event().transpose()?)
+    }
+}
+
+impl Encoder<Event> for SseCodec {
+    type Error = SseCodecError;
+
+    fn encode(&mut self, event: Event, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        let mut out = String::new();
+        encode_event(&event, &mut out)?;
+        dst.extend_from_slice(out.as_bytes());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::limits::Limit;
+    use futures::prelude::*;
+    use tokio_util::codec::{FramedRead, FramedWrite};
+
+    #[tokio::test]
+    async fn framed_read() {
+        let input: &[u8] = "\u{feff}id: 1\ndata: a\n\ndata: b".as_bytes();
+        let events = FramedRead::new(input, SseCodec::new().with_flush_on_eof(true))
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(
+            events,
+            vec![
+                Event {
+                    id: "1".to_string(),
+                    data: "a".to_string(),
+                    ..Default::default()
+                },
+                Event {
+                    id: "1".to_string(),
+                    data: "b".to_string(),
+                    ..Default::default()
+                }
+            ]
+        );
+
+        let input: &[u8] = b"data: too long\n\n";
+        let mut framed = FramedRead::new(
+            input,
+            SseCodec::new().with_limits(Limits {
+                max_line_length: Some(4),
+                ..Default::default()
+            }),
+        );
+        assert!(matches!(
+            framed.next().await,
+            Some(Err(SseCodecError::Decode(EventStreamError::LimitExceeded(
+                Limit::LineLength
+            ))))
+        ));
+    }
+
+    #[tokio::test]
+    async fn framed_write() {
+        let mut framed = FramedWrite::new(Vec::new(), SseCodec::new());
+        framed
+            .send(Event {
+                event: "add".to_string(),
+                data: "1\n2".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert!(matches!(
+            framed
+                .send(Event {
+                    id: "\n".to_string(),
+                    ..Default::default()
+                })
+                .await,
+            Err(SseCodecError::Encode(EncodeError::IdLineBreak))
+        ));
+        assert_eq!(
+            String::from_utf8(framed.into_inner()).unwrap(),
+            "event: add\ndata: 1\ndata: 2\n\n"
+        );
+    }
+}
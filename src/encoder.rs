@@ -0,0 +1,264 @@
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+
+use crate::event::Event;
+use core::fmt::{self, Write};
+
+/// Error returned when an [`Event`] cannot be represented as a `text/event-stream`
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum EncodeError {
+    /// The event name contains a line break
+    EventLineBreak,
+    /// The event id contains a line break
+    IdLineBreak,
+    /// The event id contains U+0000 NULL, which makes parsers ignore it
+    IdNull,
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EventLineBreak => f.write_str("Event name contains a line break"),
+            Self::IdLineBreak => f.write_str("Event id contains a line break"),
+            Self::IdNull => f.write_str("Event id contains a NULL character"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for EncodeError {}
+
+#[inline]
+fn has_line_break(value: &str) -> bool {
+    value.contains(['\u{000A}', '\u{000D}'])
+}
+
+/// Split text on every end-of-line, which is CRLF, CR or LF
+fn lines(text: &str) -> impl Iterator<Item = &str> {
+    let mut rest = Some(text);
+    core::iter::from_fn(move || {
+        let text = rest?;
+        match text.find(['\u{000A}', '\u{000D}']) {
+            Some(pos) => {
+                let len = if text[pos..].starts_with("\u{000D}\u{000A}") {
+                    2
+                } else {
+                    1
+                };
+                rest = Some(&text[pos + len..]);
+                Some(&text[..pos])
+            }
+            None => {
+                rest = None;
+                Some(text)
+            }
+        }
+    })
+}
+
+/// Write `event` to `out` in `text/event-stream` format, terminated by a blank line.
+///
+/// Multi-line data is written as one `data` line per line, so any CR or CRLF in it reads back as
+/// LF. An empty `event` name, `id` or missing `retry` is not written. Note that parsers carry the
+/// last event id over to following events, so an empty `id` reads back as the previous one.
+pub fn encode_event(event: &Event, out: &mut String) -> Result<(), EncodeError> {
+    if has_line_break(&event.event) {
+        return Err(EncodeError::EventLineBreak);
+    }
+    if has_line_break(&event.id) {
+        return Err(EncodeError::IdLineBreak);
+    }
+    if event.id.contains('\u{0000}') {
+        return Err(EncodeError::IdNull);
+    }
+
+    if !event.event.is_empty() {
+        write_field(out, "event", &event.event);
+    }
+    if !event.id.is_empty() {
+        write_field(out, "id", &event.id);
+    }
+    if let Some(retry) = event.retry {
+        let _ = writeln!(out, "retry: {}", retry.as_millis());
+    }
+    for line in lines(&event.data) {
+        write_field(out, "data", line);
+    }
+    out.push('\u{000A}');
+    Ok(())
+}
+
+/// Write `comment` to `out` as comment lines. Every line of the comment is written verbatim after
+/// the colon, mirroring [`crate::EventStreamItem::Comment`].
+pub fn encode_comment(comment: &str, out: &mut String) {
+    for line in lines(comment) {
+        out.push(':');
+        out.push_str(line);
+        out.push('\u{000A}');
+    }
+}
+
+#[inline]
+fn write_field(out: &mut String, name: &str, value: &str) {
+    out.push_str(name);
+    out.push_str(": ");
+    out.push_str(value);
+    out.push('\u{000A}');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EventStreamItem, Eventsource};
+    use core::time::Duration;
+    use futures::prelude::*;
+    use proptest::prelude::*;
+
+    fn encode_all(events: &[Event]) -> String {
+        let mut out = String::new();
+        for event in events {
+            encode_event(event, &mut out).unwrap();
+        }
+        out
+    }
+
+    fn parse_all(input: String) -> Vec<Event> {
+        futures::executor::block_on(
+            futures::stream::iter(vec![Ok::<_, ()>(input)])
+                .eventsource()
+                .try_collect::<Vec<_>>(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn encode() {
+        let mut out = String::new();
+        encode_event(
+            &Event {
+                event: "add".to_string(),
+                data: "line1\nline2\r\n line3".to_string(),
+                id: "42".to_string(),
+                retry: Some(Duration::from_secs(5)),
+            },
+            &mut out,
+        )
+        .unwrap();
+        assert_eq!(
+            out,
+            "event: add\nid: 42\nretry: 5000\ndata: line1\ndata: line2\ndata:  line3\n\n"
+        );
+
+        let mut out = String::new();
+        encode_event(&Event::default(), &mut out).unwrap();
+        assert_eq!(out, "data: \n\n");
+
+        // A CR is only part of a CRLF when an LF follows it
+        for (data, encoded) in [
+            ("a\r", "data: a\ndata: \n\n"),
+            ("a\r\r", "data: a\ndata: \ndata: \n\n"),
+            ("a\r\n\r", "data: a\ndata: \ndata: \n\n"),
+        ] {
+            let mut out = String::new();
+            let event = Event {
+                data: data.to_string(),
+                ..Default::default()
+            };
+            encode_event(&event, &mut out).unwrap();
+            assert_eq!(out, encoded);
+        }
+
+        let mut out = String::new();
+        encode_comment("", &mut out);
+        encode_comment(" ping\npong", &mut out);
+        assert_eq!(out, ":\n: ping\n:pong\n");
+    }
+
+    #[test]
+    fn invalid_events() {
+        let mut out = String::new();
+        let event = |event: &str, id: &str| Event {
+            event: event.to_string(),
+            id: id.to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            encode_event(&event("a\nb", ""), &mut out),
+            Err(EncodeError::EventLineBreak)
+        );
+        assert_eq!(
+            encode_event(&event("", "1\r"), &mut out),
+            Err(EncodeError::IdLineBreak)
+        );
+        assert_eq!(
+            encode_event(&event("", "1\0"), &mut out),
+            Err(EncodeError::IdNull)
+        );
+        assert_eq!(out, "");
+    }
+
+    #[test]
+    fn comment_round_trip() {
+        let mut out = String::new();
+        encode_comment(" ping", &mut out);
+        let items = futures::executor::block_on(
+            futures::stream::iter(vec![Ok::<_, ()>(out)])
+                .eventsource()
+                .extended()
+                .try_collect::<Vec<_>>(),
+        )
+        .unwrap();
+        assert_eq!(items, vec![EventStreamItem::Comment(" ping".to_string())]);
+    }
+
+    /// Data as read back by the parser, with every CRLF and CR turned into LF
+    fn normalize(data: &str) -> String {
+        data.replace("\r\n", "\n").replace('\r', "\n")
+    }
+
+    fn event_strategy() -> impl Strategy<Value = Event> {
+        (
+            "[^\r\n]*",
+            "(.|\r|\n)*",
+            "[^\r\n\u{0}]*",
+            proptest::option::of(any::<u32>()),
+        )
+            .prop_map(|(event, data, id, retry)| Event {
+                event,
+                data,
+                id,
+                retry: retry.map(|retry| Duration::from_millis(retry as u64)),
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn round_trip(event in event_strategy()) {
+            let expected = Event {
+                data: normalize(&event.data),
+                ..event.clone()
+            };
+            prop_assert_eq!(parse_all(encode_all(core::slice::from_ref(&event))), vec![expected]);
+        }
+
+        #[test]
+        fn round_trip_stream(events in proptest::collection::vec(event_strategy(), 0..8)) {
+            let mut last_event_id = String::new();
+            let expected = events
+                .iter()
+                .map(|event| {
+                    if !event.id.is_empty() {
+                        last_event_id = event.id.clone();
+                    }
+                    Event {
+                        id: last_event_id.clone(),
+                        event: event.event.clone(),
+                        data: normalize(&event.data),
+                        retry: event.retry,
+                    }
+                })
+                .collect::<Vec<_>>();
+            prop_assert_eq!(parse_all(encode_all(&events)), expected);
+        }
+    }
+}
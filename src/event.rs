@@ -1,10 +1,14 @@
 #[cfg(not(feature = "std"))]
-use alloc::{string::String, vec::Vec};
+use alloc::string::{String, ToString};
 
 use core::time::Duration;
 
 /// An Event
-#[derive(Default, Debug, Eq, PartialEq)]
+///
+/// With the `serde` feature it can be serialized, with `retry` as a number of milliseconds.
+#[derive(Default, Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Event {
     /// The event name if given
     pub event: String,
@@ -13,5 +17,108 @@ pub struct Event {
     /// The event id if given
     pub id: String,
     /// Retry duration if given
+    #[cfg_attr(feature = "serde", serde(with = "retry_millis"))]
     pub retry: Option<Duration>,
 }
+
+#[cfg(feature = "serde")]
+mod retry_millis {
+    use core::convert::TryFrom;
+    use core::time::Duration;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        retry: &Option<Duration>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        retry
+            .map(|retry| u64::try_from(retry.as_millis()).unwrap_or(u64::MAX))
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Duration>, D::Error> {
+        Ok(Option::<u64>::deserialize(deserializer)?.map(Duration::from_millis))
+    }
+}
+
+/// An event borrowing its fields, see [`crate::Parser::next_event_ref`]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct EventRef<'a> {
+    /// The event name if given
+    pub event: &'a str,
+    /// The event data
+    pub data: &'a str,
+    /// The event id if given
+    pub id: &'a str,
+    /// Retry duration if given
+    pub retry: Option<Duration>,
+}
+
+impl<'a> From<&'a Event> for EventRef<'a> {
+    fn from(event: &'a Event) -> Self {
+        Self {
+            event: &event.event,
+            data: &event.data,
+            id: &event.id,
+            retry: event.retry,
+        }
+    }
+}
+
+impl From<EventRef<'_>> for Event {
+    fn from(event: EventRef<'_>) -> Self {
+        Self {
+            event: event.event.to_string(),
+            data: event.data.to_string(),
+            id: event.id.to_string(),
+            retry: event.retry,
+        }
+    }
+}
+
+/// An item of an [`crate::ExtendedEventStream`]
+#[derive(Debug, Eq, PartialEq)]
+pub enum EventStreamItem {
+    /// A dispatched event
+    Event(Event),
+    /// A comment line without the leading colon, such as a ` ping` heartbeat
+    Comment(String),
+    /// A field which is not part of the spec, as name and value
+    Field(String, String),
+    /// A valid `retry` field changed the reconnection time. A `retry` field repeating the
+    /// current reconnection time is not returned.
+    Retry(Duration),
+    /// A blank line changed the last event ID without dispatching an event, because the block
+    /// had no `data` field
+    Id(String),
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serde() {
+        let event = Event {
+            event: "add".to_string(),
+            data: "1\n2".to_string(),
+            id: "42".to_string(),
+            retry: Some(Duration::from_millis(1500)),
+        };
+        let json = serde_json::to_string(&event).unwrap();
+        assert_eq!(
+            json,
+            r#"{"event":"add","data":"1\n2","id":"42","retry":1500}"#
+        );
+        assert_eq!(serde_json::from_str::<Event>(&json).unwrap(), event);
+        assert_eq!(
+            serde_json::from_str::<Event>(r#"{"data":"x","retry":null}"#).unwrap(),
+            Event {
+                data: "x".to_string(),
+                ..Default::default()
+            }
+        );
+    }
+}
@@ -0,0 +1,221 @@
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+#[cfg(feature = "std")]
+use std::io::{self, Read};
+
+use crate::event::Event;
+use crate::event_stream::EventStreamError;
+use crate::limits::Limits;
+use crate::push_parser::{Parser, StreamEnd};
+use crate::utf8::Utf8Policy;
+use core::time::Duration;
+
+/// A blocking Iterator of events, the synchronous counterpart of [`crate::EventStream`]
+///
+/// It is created from an iterator of byte chunks with [`EventIter::new`] or from a reader with
+/// [`EventIter::from_reader`], and parses them exactly like an [`crate::EventStream`].
+pub struct EventIter<I> {
+    iter: I,
+    parser: Parser,
+}
+
+impl<I> EventIter<I> {
+    /// Create an event iterator from an iterator of byte chunks
+    pub fn new<T>(iter: T) -> Self
+    where
+        T: IntoIterator<IntoIter = I>,
+    {
+        Self {
+            iter: iter.into_iter(),
+            parser: Parser::new(),
+        }
+    }
+
+    /// Start from a known last event ID, see [`crate::EventStream::with_last_event_id`]
+    pub fn with_last_event_id(mut self, last_event_id: impl Into<String>) -> Self {
+        self.parser = self.parser.with_last_event_id(last_event_id);
+        self
+    }
+
+    /// Start from a known reconnection time, see [`crate::EventStream::with_retry`]
+    pub fn with_retry(mut self, retry: Duration) -> Self {
+        self.parser = self.parser.with_retry(retry);
+        self
+    }
+
+    /// Dispatch a partly built event at the end of the source, see
+    /// [`crate::EventStream::with_flush_on_eof`]
+    pub fn with_flush_on_eof(mut self, flush_on_eof: bool) -> Self {
+        self.parser = self.parser.with_flush_on_eof(flush_on_eof);
+        self
+    }
+
+    /// Set how invalid UTF-8 in the source is handled. Defaults to [`Utf8Policy::Lossy`].
+    pub fn with_utf8_policy(mut self, policy: Utf8Policy) -> Self {
+        self.parser = self.parser.with_utf8_policy(policy);
+        self
+    }
+
+    /// Set the size limits applied to lines, events and buffered data
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.parser = self.parser.with_limits(limits);
+        self
+    }
+
+    /// Get the last event ID of the iterator
+    pub fn last_event_id(&self) -> &str {
+        self.parser.last_event_id()
+    }
+
+    /// Get the reconnection time last set by a `retry` field
+    pub fn retry(&self) -> Option<Duration> {
+        self.parser.retry()
+    }
+
+    /// Get how the source ended, or `None` if it has not ended yet, see [`Parser::stream_end`]
+    pub fn stream_end(&self) -> Option<StreamEnd> {
+        self.parser.stream_end()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: Read> EventIter<ReadChunks<R>> {
+    /// Create an event iterator reading from `reader`, such as a `TcpStream` or a file
+    pub fn from_reader(reader: R) -> Self {
+        Self::new(ReadChunks::new(reader))
+    }
+}
+
+impl<I, B, E> Iterator for EventIter<I>
+where
+    I: Iterator<Item = Result<B, E>>,
+    B: AsRef<[u8]>,
+{
+    type Item = Result<Event, EventStreamError<E>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(event) = self.parser.next_event() {
+                return Some(event.map_err(EventStreamError::into_transport));
+            }
+            if self.parser.is_terminated() {
+                return None;
+            }
+            match self.iter.next() {
+                Some(Ok(bytes)) => self.parser.push(bytes.as_ref()),
+                Some(Err(err)) => return Some(Err(EventStreamError::Transport(err))),
+                None => self.parser.finish(),
+            }
+        }
+    }
+}
+
+/// An Iterator over the chunks of bytes read from a reader, see [`EventIter::from_reader`]
+#[cfg(feature = "std")]
+pub struct ReadChunks<R> {
+    reader: R,
+    buffer: Box<[u8]>,
+}
+
+#[cfg(feature = "std")]
+impl<R> ReadChunks<R> {
+    fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buffer: vec![0; 8 * 1024].into_boxed_slice(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: Read> Iterator for ReadChunks<R> {
+    type Item = io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.reader.read(&mut self.buffer) {
+                Ok(0) => return None,
+                Ok(len) => return Some(Ok(self.buffer[..len].to_vec())),
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let len = self.0.len().min(buf.len()).min(3);
+            buf[..len].copy_from_slice(&self.0[..len]);
+            self.0 = &self.0[len..];
+            Ok(len)
+        }
+    }
+
+    #[test]
+    fn iter_events() {
+        assert_eq!(
+            EventIter::new(vec![
+                Ok::<_, ()>("data: Hello,"),
+                Ok::<_, ()>(" world!\n\n")
+            ])
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap(),
+            vec![Event {
+                data: "Hello, world!".to_string(),
+                ..Default::default()
+            }]
+        );
+
+        let mut iter = EventIter::from_reader(Trickle(
+            "\u{feff}event: add\nid: 1\ndata: 👍\n\ndata: last".as_bytes(),
+        ))
+        .with_flush_on_eof(true);
+        assert_eq!(
+            iter.by_ref().collect::<Result<Vec<_>, _>>().unwrap(),
+            vec![
+                Event {
+                    event: "add".to_string(),
+                    id: "1".to_string(),
+                    data: "👍".to_string(),
+                    ..Default::default()
+                },
+                Event {
+                    id: "1".to_string(),
+                    data: "last".to_string(),
+                    ..Default::default()
+                }
+            ]
+        );
+        assert_eq!(iter.stream_end(), Some(StreamEnd::MidEvent));
+    }
+
+    #[test]
+    fn iter_errors() {
+        let results =
+            EventIter::new(vec![Ok(b"data: a\n\n".to_vec()), Err("closed")]).collect::<Vec<_>>();
+        assert_eq!(
+            results,
+            vec![
+                Ok(Event {
+                    data: "a".to_string(),
+                    ..Default::default()
+                }),
+                Err(EventStreamError::Transport("closed"))
+            ]
+        );
+
+        let results = EventIter::new(vec![Ok::<_, ()>(b"data: a\n\nd\xf0\x9f".to_vec())])
+            .with_utf8_policy(Utf8Policy::Strict)
+            .collect::<Vec<_>>();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].as_ref().unwrap().data, "a");
+        assert!(matches!(results[1], Err(EventStreamError::Utf8(_))));
+    }
+}
@@ -0,0 +1,290 @@
+use crate::encoder::{encode_comment, encode_event, EncodeError};
+use crate::event::Event;
+use core::fmt;
+use core::pin::Pin;
+use futures_core::ready;
+use futures_core::stream::Stream;
+use futures_core::task::{Context, Poll};
+use futures_io::AsyncWrite;
+use futures_sink::Sink;
+use pin_project::pin_project;
+use std::io;
+
+type Heartbeat = Pin<Box<dyn Stream<Item = ()> + Send>>;
+
+#[pin_project]
+struct Ticks<T>(#[pin] T);
+
+impl<T: Stream> Stream for Ticks<T> {
+    type Item = ();
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        self.project().0.poll_next(cx).map(|tick| tick.map(|_| ()))
+    }
+}
+
+/// A [`Sink`] of events which writes them in `text/event-stream` format to an [`AsyncWrite`]
+///
+/// Events are buffered until the sink is flushed or becomes ready for the next event. To write
+/// to a sink of byte chunks instead, wrap it in a [`SinkWriter`].
+#[pin_project]
+pub struct EventSink<W> {
+    #[pin]
+    writer: W,
+    buffer: String,
+    pos: usize,
+    heartbeat: Option<Heartbeat>,
+}
+
+impl<W> EventSink<W> {
+    /// Create an event sink writing to `writer`
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            buffer: String::new(),
+            pos: 0,
+            heartbeat: None,
+        }
+    }
+
+    /// Write an empty comment line every time `ticks` yields, for example from an interval
+    /// timer, to keep idle connections alive. Heartbeats are written whenever the sink is polled,
+    /// which includes flushing while idle in [`futures::StreamExt::forward`].
+    ///
+    /// [`futures::StreamExt::forward`]: https://docs.rs/futures/0.3/futures/stream/trait.StreamExt.html#method.forward
+    pub fn with_heartbeat<T>(mut self, ticks: T) -> Self
+    where
+        T: Stream + Send + 'static,
+    {
+        self.heartbeat = Some(Box::pin(Ticks(ticks)));
+        self
+    }
+
+    /// Get a reference to the underlying writer
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Consume the sink, returning the underlying writer. Buffered events which were not flushed
+    /// yet are lost.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: AsyncWrite> EventSink<W> {
+    fn poll_write_buffer(self: Pin<&mut Self>, cx: &mut Context) -> Poll<io::Result<()>> {
+        let mut this = self.project();
+        if let Some(heartbeat) = this.heartbeat.as_mut() {
+            loop {
+                match heartbeat.as_mut().poll_next(cx) {
+                    Poll::Ready(Some(())) => encode_comment("", this.buffer),
+                    Poll::Ready(None) => {
+                        *this.heartbeat = None;
+                        break;
+                    }
+                    Poll::Pending => break,
+                }
+            }
+        }
+        while *this.pos < this.buffer.len() {
+            let written = ready!(this
+                .writer
+                .as_mut()
+                .poll_write(cx, &this.buffer.as_bytes()[*this.pos..]))?;
+            if written == 0 {
+                return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+            }
+            *this.pos += written;
+        }
+        this.buffer.clear();
+        *this.pos = 0;
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Error returned by an [`EventSink`]
+#[derive(Debug)]
+pub enum EventSinkError {
+    /// The event cannot be encoded
+    Encode(EncodeError),
+    /// Underlying writer error
+    Io(io::Error),
+}
+
+impl From<EncodeError> for EventSinkError {
+    fn from(err: EncodeError) -> Self {
+        Self::Encode(err)
+    }
+}
+
+impl From<io::Error> for EventSinkError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl fmt::Display for EventSinkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Encode(err) => f.write_fmt(format_args!("Encode error: {}", err)),
+            Self::Io(err) => f.write_fmt(format_args!("IO error: {}", err)),
+        }
+    }
+}
+
+impl std::error::Error for EventSinkError {}
+
+impl<W: AsyncWrite> Sink<Event> for EventSink<W> {
+    type Error = EventSinkError;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Self::Error>> {
+        self.poll_write_buffer(cx).map_err(Into::into)
+    }
+
+    fn start_send(self: Pin<&mut Self>, event: Event) -> Result<(), Self::Error> {
+        let this = self.project();
+        let len = this.buffer.len();
+        encode_event(&event, this.buffer).map_err(|err| {
+            this.buffer.truncate(len);
+            err.into()
+        })
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Self::Error>> {
+        ready!(self.as_mut().poll_write_buffer(cx))?;
+        self.project().writer.poll_flush(cx).map_err(Into::into)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<(), Self::Error>> {
+        ready!(self.as_mut().poll_write_buffer(cx))?;
+        self.project().writer.poll_close(cx).map_err(Into::into)
+    }
+}
+
+/// An [`AsyncWrite`] which sends every write as one byte chunk to a [`Sink`], such as the sender
+/// of a channel backing a response body
+#[pin_project]
+pub struct SinkWriter<S> {
+    #[pin]
+    sink: S,
+}
+
+impl<S> SinkWriter<S> {
+    /// Create a writer sending byte chunks to `sink`
+    pub fn new(sink: S) -> Self {
+        Self { sink }
+    }
+
+    /// Consume the writer, returning the underlying sink
+    pub fn into_inner(self) -> S {
+        self.sink
+    }
+}
+
+fn sink_error<E>(err: E) -> io::Error
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    io::Error::other(err)
+}
+
+impl<S, E> AsyncWrite for SinkWriter<S>
+where
+    S: Sink<Vec<u8>, Error = E>,
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let mut this = self.project();
+        ready!(this.sink.as_mut().poll_ready(cx)).map_err(sink_error)?;
+        this.sink.start_send(buf.to_vec()).map_err(sink_error)?;
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.project().sink.poll_flush(cx).map_err(sink_error)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.project().sink.poll_close(cx).map_err(sink_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::time::Duration;
+    use futures::prelude::*;
+
+    #[tokio::test]
+    async fn write_events() {
+        let mut sink = EventSink::new(Vec::new());
+        sink.send(Event {
+            event: "add".to_string(),
+            data: "1\n2".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        assert!(matches!(
+            sink.send(Event {
+                id: "\0".to_string(),
+                ..Default::default()
+            })
+            .await,
+            Err(EventSinkError::Encode(EncodeError::IdNull))
+        ));
+        sink.send(Event {
+            data: "3".to_string(),
+            retry: Some(Duration::from_millis(10)),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(sink.into_inner()).unwrap(),
+            "event: add\ndata: 1\ndata: 2\n\nretry: 10\ndata: 3\n\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn heartbeat() {
+        let mut sink = EventSink::new(Vec::new()).with_heartbeat(stream::iter(vec![(), ()]));
+        sink.flush().await.unwrap();
+        sink.send(Event {
+            data: "1".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(sink.into_inner()).unwrap(),
+            ":\n:\ndata: 1\n\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn chunk_sink() {
+        let mut sink = EventSink::new(SinkWriter::new(Vec::<Vec<u8>>::new()));
+        sink.send_all(&mut stream::iter(vec![
+            Ok(Event {
+                data: "1".to_string(),
+                ..Default::default()
+            }),
+            Ok(Event {
+                data: "2".to_string(),
+                ..Default::default()
+            }),
+        ]))
+        .await
+        .unwrap();
+        assert_eq!(
+            sink.into_inner().into_inner(),
+            vec![b"data: 1\n\n".to_vec(), b"data: 2\n\n".to_vec()]
+        );
+    }
+}
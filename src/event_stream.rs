@@ -1,157 +1,99 @@
 #[cfg(not(feature = "std"))]
-use alloc::{
-    string::{FromUtf8Error, String, ToString},
-    vec::Vec,
-};
+use alloc::string::{FromUtf8Error, String, ToString};
 
 #[cfg(feature = "std")]
 use std::string::FromUtf8Error;
 
-use crate::event::Event;
-use crate::parser::{is_bom, is_lf, line, RawEventLine};
-use crate::utf8_stream::{Utf8Stream, Utf8StreamError};
+use crate::event::{Event, EventStreamItem};
+use crate::limits::{Limit, Limits};
+use crate::push_parser::{Parser, StreamEnd};
+use crate::utf8::Utf8Policy;
+use core::convert::Infallible;
 use core::fmt;
 use core::pin::Pin;
 use core::time::Duration;
+use futures_core::ready;
 use futures_core::stream::Stream;
 use futures_core::task::{Context, Poll};
 use nom::error::Error as NomError;
 use pin_project::pin_project;
 
-#[derive(Default, Debug)]
-struct EventBuilder {
-    event: Event,
-    is_complete: bool,
+/// A Stream of events
+#[pin_project]
+pub struct EventStream<S> {
+    #[pin]
+    stream: S,
+    parser: Parser,
 }
 
-impl EventBuilder {
-    /// From the HTML spec
-    ///
-    /// -> If the field name is "event"
-    ///    Set the event type buffer to field value.
-    ///
-    /// -> If the field name is "data"
-    ///    Append the field value to the data buffer, then append a single U+000A LINE FEED (LF)
-    ///    character to the data buffer.
-    ///
-    /// -> If the field name is "id"
-    ///    If the field value does not contain U+0000 NULL, then set the last event ID buffer
-    ///    to the field value. Otherwise, ignore the field.
-    ///
-    /// -> If the field name is "retry"
-    ///    If the field value consists of only ASCII digits, then interpret the field value as
-    ///    an integer in base ten, and set the event stream's reconnection time to that integer.
-    ///    Otherwise, ignore the field.
-    ///
-    /// -> Otherwise
-    ///    The field is ignored.
-    fn add(&mut self, line: RawEventLine) {
-        match line {
-            RawEventLine::Field(field, val) => {
-                let val = val.unwrap_or("");
-                match field {
-                    "event" => {
-                        self.event.event = val.to_string();
-                    }
-                    "data" => {
-                        self.event.data.push_str(val);
-                        self.event.data.push('\u{000A}');
-                    }
-                    "id" => {
-                        if !val.contains('\u{0000}') {
-                            self.event.id = val.to_string()
-                        }
-                    }
-                    "retry" => {
-                        if let Ok(val) = val.parse::<u64>() {
-                            self.event.retry = Some(Duration::from_millis(val))
-                        }
-                    }
-                    _ => {}
-                }
-            }
-            RawEventLine::Comment(_) => {}
-            RawEventLine::Empty => self.is_complete = true,
+impl<S> EventStream<S> {
+    pub(crate) fn new(stream: S) -> Self {
+        Self {
+            stream,
+            parser: Parser::new(),
         }
     }
 
-    /// From the HTML spec
-    ///
-    /// 1. Set the last event ID string of the event source to the value of the last event ID
-    /// buffer. The buffer does not get reset, so the last event ID string of the event source
-    /// remains set to this value until the next time it is set by the server.
-    /// 2. If the data buffer is an empty string, set the data buffer and the event type buffer
-    /// to the empty string and return.
-    /// 3. If the data buffer's last character is a U+000A LINE FEED (LF) character, then remove
-    /// the last character from the data buffer.
-    /// 4. Let event be the result of creating an event using MessageEvent, in the relevant Realm
-    /// of the EventSource object.
-    /// 5. Initialize event's type attribute to message, its data attribute to data, its origin
-    /// attribute to the serialization of the origin of the event stream's final URL (i.e., the
-    /// URL after redirects), and its lastEventId attribute to the last event ID string of the
-    /// event source.
-    /// 6. If the event type buffer has a value other than the empty string, change the type of
-    /// the newly created event to equal the value of the event type buffer.
-    /// 7. Set the data buffer and the event type buffer to the empty string.
-    /// 8. Queue a task which, if the readyState attribute is set to a value other than CLOSED,
-    /// dispatches the newly created event at the EventSource object.
-    fn dispatch(&mut self) -> Option<Event> {
-        let builder = core::mem::take(self);
-        let mut event = builder.event;
-        self.event.id = event.id.clone();
-
-        if event.data.is_empty() {
-            return None;
-        }
-
-        if is_lf(event.data.chars().next_back().unwrap()) {
-            event.data.pop();
-        }
-        Some(event)
+    /// Start from a known last event ID, for example one persisted before a restart. Events
+    /// without an `id` field carry it until the server sends a new one.
+    pub fn with_last_event_id(mut self, last_event_id: impl Into<String>) -> Self {
+        self.parser = self.parser.with_last_event_id(last_event_id);
+        self
     }
-}
 
-#[derive(Debug, Clone, Copy)]
-pub enum EventStreamState {
-    NotStarted,
-    Started,
-    Terminated,
-}
+    /// Start from a known reconnection time, which is returned by [`EventStream::retry`] until
+    /// the server sends a `retry` field
+    pub fn with_retry(mut self, retry: Duration) -> Self {
+        self.parser = self.parser.with_retry(retry);
+        self
+    }
 
-impl EventStreamState {
-    fn is_terminated(self) -> bool {
-        matches!(self, Self::Terminated)
+    /// Treat the end of the source stream as a final line terminator, dispatching any partly
+    /// built event instead of discarding it. Disabled by default.
+    pub fn with_flush_on_eof(mut self, flush_on_eof: bool) -> Self {
+        self.parser = self.parser.with_flush_on_eof(flush_on_eof);
+        self
     }
-    fn is_started(self) -> bool {
-        matches!(self, Self::Started)
+
+    /// Set how invalid UTF-8 in the source stream is handled. Defaults to
+    /// [`Utf8Policy::Lossy`].
+    pub fn with_utf8_policy(mut self, policy: Utf8Policy) -> Self {
+        self.parser = self.parser.with_utf8_policy(policy);
+        self
     }
-}
 
-/// A Stream of events
-#[pin_project]
-pub struct EventStream<S> {
-    #[pin]
-    stream: Utf8Stream<S>,
-    buffer: String,
-    builder: EventBuilder,
-    state: EventStreamState,
-    last_event_id: String,
-}
+    /// Set the size limits applied to lines, events and buffered data
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.parser = self.parser.with_limits(limits);
+        self
+    }
 
-impl<S> EventStream<S> {
-    pub(crate) fn new(stream: S) -> Self {
-        Self {
-            stream: Utf8Stream::new(stream),
-            buffer: String::new(),
-            builder: EventBuilder::default(),
-            state: EventStreamState::NotStarted,
-            last_event_id: String::new(),
-        }
+    /// Also yield comments and fields which are not part of the spec, next to the dispatched
+    /// events
+    pub fn extended(mut self) -> ExtendedEventStream<S> {
+        self.parser = self.parser.with_extended(true);
+        ExtendedEventStream { stream: self }
     }
 
     /// Get the last event ID of the stream
     pub fn last_event_id(&self) -> &str {
-        &self.last_event_id
+        self.parser.last_event_id()
+    }
+
+    /// Get the reconnection time last set by a `retry` field, whether or not an event was
+    /// dispatched with it
+    pub fn retry(&self) -> Option<Duration> {
+        self.parser.retry()
+    }
+
+    /// Get how the source stream ended, or `None` if it has not ended yet, see [`Parser::stream_end`]
+    pub fn stream_end(&self) -> Option<StreamEnd> {
+        self.parser.stream_end()
+    }
+
+    /// Get a reference to the source stream
+    pub fn get_ref(&self) -> &S {
+        &self.stream
     }
 }
 
@@ -164,13 +106,18 @@ pub enum EventStreamError<E> {
     Parser(NomError<String>),
     /// Underlying source stream error
     Transport(E),
+    /// A configured size limit was exceeded
+    LimitExceeded(Limit),
 }
 
-impl<E> From<Utf8StreamError<E>> for EventStreamError<E> {
-    fn from(err: Utf8StreamError<E>) -> Self {
-        match err {
-            Utf8StreamError::Utf8(err) => Self::Utf8(err),
-            Utf8StreamError::Transport(err) => Self::Transport(err),
+impl EventStreamError<Infallible> {
+    /// Widen an error of the [`Parser`], which has no transport, to that of a stream
+    pub(crate) fn into_transport<E>(self) -> EventStreamError<E> {
+        match self {
+            Self::Utf8(err) => EventStreamError::Utf8(err),
+            Self::Parser(err) => EventStreamError::Parser(err),
+            Self::Transport(err) => match err {},
+            Self::LimitExceeded(limit) => EventStreamError::LimitExceeded(limit),
         }
     }
 }
@@ -190,6 +137,7 @@ where
             Self::Utf8(err) => f.write_fmt(format_args!("UTF8 error: {}", err)),
             Self::Parser(err) => f.write_fmt(format_args!("Parse error: {}", err)),
             Self::Transport(err) => f.write_fmt(format_args!("Transport error: {}", err)),
+            Self::LimitExceeded(limit) => f.write_fmt(format_args!("Exceeded {}", limit)),
         }
     }
 }
@@ -197,28 +145,28 @@ where
 #[cfg(feature = "std")]
 impl<E> std::error::Error for EventStreamError<E> where E: fmt::Display + fmt::Debug + Send + Sync {}
 
-fn parse_event<E>(
-    buffer: &mut String,
-    builder: &mut EventBuilder,
-) -> Result<Option<Event>, EventStreamError<E>> {
-    if buffer.is_empty() {
-        return Ok(None);
-    }
-    loop {
-        match line(buffer.as_ref()) {
-            Ok((rem, next_line)) => {
-                builder.add(next_line);
-                let consumed = buffer.len() - rem.len();
-                let rem = buffer.split_off(consumed);
-                *buffer = rem;
-                if builder.is_complete {
-                    if let Some(event) = builder.dispatch() {
-                        return Ok(Some(event));
-                    }
-                }
+impl<S, B, E> EventStream<S>
+where
+    S: Stream<Item = Result<B, E>>,
+    B: AsRef<[u8]>,
+{
+    fn poll_item(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+    ) -> Poll<Option<Result<EventStreamItem, EventStreamError<E>>>> {
+        let mut this = self.project();
+        loop {
+            if let Some(item) = this.parser.next_item() {
+                return Poll::Ready(Some(item.map_err(EventStreamError::into_transport)));
+            }
+            if this.parser.is_terminated() {
+                return Poll::Ready(None);
+            }
+            match ready!(this.stream.as_mut().poll_next(cx)) {
+                Some(Ok(bytes)) => this.parser.push(bytes.as_ref()),
+                Some(Err(err)) => return Poll::Ready(Some(Err(EventStreamError::Transport(err)))),
+                None => this.parser.finish(),
             }
-            Err(nom::Err::Incomplete(_)) => return Ok(None),
-            Err(nom::Err::Error(err)) | Err(nom::Err::Failure(err)) => return Err(err.into()),
         }
     }
 }
@@ -230,64 +178,61 @@ where
 {
     type Item = Result<Event, EventStreamError<E>>;
 
-    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
-        let mut this = self.project();
-
-        match parse_event(this.buffer, this.builder) {
-            Ok(Some(event)) => {
-                *this.last_event_id = event.id.clone();
-                return Poll::Ready(Some(Ok(event)));
-            }
-            Err(err) => return Poll::Ready(Some(Err(err))),
-            _ => {}
-        }
-
-        if this.state.is_terminated() {
-            return Poll::Ready(None);
-        }
-
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
         loop {
-            match this.stream.as_mut().poll_next(cx) {
-                Poll::Ready(Some(Ok(string))) => {
-                    if string.is_empty() {
-                        continue;
-                    }
-
-                    let slice = if this.state.is_started() {
-                        &string
-                    } else {
-                        *this.state = EventStreamState::Started;
-                        if is_bom(string.chars().next().unwrap()) {
-                            &string[1..]
-                        } else {
-                            &string
-                        }
-                    };
-                    this.buffer.push_str(slice);
-
-                    match parse_event(this.buffer, this.builder) {
-                        Ok(Some(event)) => {
-                            *this.last_event_id = event.id.clone();
-                            return Poll::Ready(Some(Ok(event)));
-                        }
-                        Err(err) => return Poll::Ready(Some(Err(err))),
-                        _ => {}
-                    }
-                }
-                Poll::Ready(Some(Err(err))) => return Poll::Ready(Some(Err(err.into()))),
-                Poll::Ready(None) => {
-                    *this.state = EventStreamState::Terminated;
-                    return Poll::Ready(None);
+            match self.as_mut().poll_item(cx) {
+                Poll::Ready(Some(Ok(EventStreamItem::Event(event)))) => {
+                    return Poll::Ready(Some(Ok(event)))
                 }
+                Poll::Ready(Some(Ok(_))) => {}
+                Poll::Ready(Some(Err(err))) => return Poll::Ready(Some(Err(err))),
+                Poll::Ready(None) => return Poll::Ready(None),
                 Poll::Pending => return Poll::Pending,
             }
         }
     }
 }
 
+/// A Stream of events, comments and unknown fields, see [`EventStream::extended`]
+#[pin_project]
+pub struct ExtendedEventStream<S> {
+    #[pin]
+    stream: EventStream<S>,
+}
+
+impl<S> ExtendedEventStream<S> {
+    /// Get the underlying event stream
+    pub fn get_ref(&self) -> &EventStream<S> {
+        &self.stream
+    }
+
+    /// Get the last event ID of the stream
+    pub fn last_event_id(&self) -> &str {
+        self.stream.last_event_id()
+    }
+
+    /// Get the reconnection time last set by a `retry` field
+    pub fn retry(&self) -> Option<Duration> {
+        self.stream.retry()
+    }
+}
+
+impl<S, B, E> Stream for ExtendedEventStream<S>
+where
+    S: Stream<Item = Result<B, E>>,
+    B: AsRef<[u8]>,
+{
+    type Item = Result<EventStreamItem, EventStreamError<E>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        self.project().stream.poll_item(cx)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::limits::LimitAction;
     use futures::prelude::*;
 
     #[tokio::test]
@@ -372,6 +317,318 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn flush_on_eof() {
+        let mut stream = EventStream::new(futures::stream::iter(vec![Ok::<_, ()>(
+            "data: Hello, world!\n",
+        )]));
+        assert_eq!(
+            stream.by_ref().try_collect::<Vec<_>>().await.unwrap(),
+            vec![]
+        );
+        assert_eq!(stream.stream_end(), Some(StreamEnd::MidEvent));
+
+        let mut stream = EventStream::new(futures::stream::iter(vec![Ok::<_, ()>(
+            "data: Hello,\ndata: world!",
+        )]))
+        .with_flush_on_eof(true);
+        assert_eq!(
+            stream.by_ref().try_collect::<Vec<_>>().await.unwrap(),
+            vec![Event {
+                data: "Hello,\nworld!".to_string(),
+                ..Default::default()
+            }]
+        );
+        assert_eq!(stream.stream_end(), Some(StreamEnd::MidEvent));
+
+        let mut stream = EventStream::new(futures::stream::iter(vec![Ok::<_, ()>(
+            "data: first\n\nid: 2\ndata: second\r",
+        )]))
+        .with_flush_on_eof(true);
+        assert_eq!(
+            stream.by_ref().try_collect::<Vec<_>>().await.unwrap(),
+            vec![
+                Event {
+                    data: "first".to_string(),
+                    ..Default::default()
+                },
+                Event {
+                    id: "2".to_string(),
+                    data: "second".to_string(),
+                    ..Default::default()
+                }
+            ]
+        );
+        assert_eq!(stream.last_event_id(), "2");
+        assert_eq!(stream.stream_end(), Some(StreamEnd::MidEvent));
+
+        let mut stream = EventStream::new(futures::stream::iter(vec![Ok::<_, ()>(
+            "data: Hello, world!\n\n",
+        )]))
+        .with_flush_on_eof(true);
+        assert_eq!(
+            stream.by_ref().try_collect::<Vec<_>>().await.unwrap(),
+            vec![Event {
+                data: "Hello, world!".to_string(),
+                ..Default::default()
+            }]
+        );
+        assert_eq!(stream.stream_end(), Some(StreamEnd::Clean));
+    }
+
+    #[tokio::test]
+    async fn invalid_utf8() {
+        let chunks = || {
+            futures::stream::iter(vec![
+                Ok::<_, ()>(b"data: a\xff\n".to_vec()),
+                Ok::<_, ()>(b"data: b\n\n".to_vec()),
+            ])
+        };
+        assert_eq!(
+            EventStream::new(chunks())
+                .try_collect::<Vec<_>>()
+                .await
+                .unwrap(),
+            vec![Event {
+                data: "a\u{fffd}\nb".to_string(),
+                ..Default::default()
+            }]
+        );
+        assert_eq!(
+            EventStream::new(chunks())
+                .with_utf8_policy(Utf8Policy::SkipLine)
+                .try_collect::<Vec<_>>()
+                .await
+                .unwrap(),
+            vec![Event {
+                data: "b".to_string(),
+                ..Default::default()
+            }]
+        );
+        let results = EventStream::new(futures::stream::iter(vec![Ok::<_, ()>(
+            b"data: z\n\ndata: a\xff\n\n".to_vec(),
+        )]))
+        .with_utf8_policy(Utf8Policy::Strict)
+        .collect::<Vec<_>>()
+        .await;
+        assert_eq!(results.len(), 2);
+        assert_eq!(
+            results[0],
+            Ok(Event {
+                data: "z".to_string(),
+                ..Default::default()
+            })
+        );
+        assert!(matches!(results[1], Err(EventStreamError::Utf8(_))));
+    }
+
+    #[tokio::test]
+    async fn limits() {
+        let line_limit = |on_exceeded| Limits {
+            max_line_length: Some(10),
+            on_exceeded,
+            ..Default::default()
+        };
+        assert_eq!(
+            EventStream::new(futures::stream::iter(vec![Ok::<_, ()>(
+                "data: 0123456789\n\ndata: ok\n\n"
+            )]))
+            .with_limits(line_limit(LimitAction::Fail))
+            .collect::<Vec<_>>()
+            .await,
+            vec![Err(EventStreamError::LimitExceeded(Limit::LineLength))]
+        );
+        assert_eq!(
+            EventStream::new(futures::stream::iter(vec![
+                Ok::<_, ()>("data: ok1\n\ndata: 0123456"),
+                Ok::<_, ()>("789"),
+                Ok::<_, ()>("\r\ndata: x\n\ndata: ok2\n\n")
+            ]))
+            .with_limits(line_limit(LimitAction::Skip))
+            .collect::<Vec<_>>()
+            .await,
+            vec![
+                Ok(Event {
+                    data: "ok1".to_string(),
+                    ..Default::default()
+                }),
+                Err(EventStreamError::LimitExceeded(Limit::LineLength)),
+                Ok(Event {
+                    data: "ok2".to_string(),
+                    ..Default::default()
+                })
+            ]
+        );
+        assert_eq!(
+            EventStream::new(futures::stream::iter(vec![Ok::<_, ()>(
+                "event: a\nid: 1\ndata: x\n\ndata: y\n\n"
+            )]))
+            .with_limits(Limits {
+                max_fields: Some(2),
+                on_exceeded: LimitAction::Skip,
+                ..Default::default()
+            })
+            .collect::<Vec<_>>()
+            .await,
+            vec![
+                Err(EventStreamError::LimitExceeded(Limit::Fields)),
+                Ok(Event {
+                    data: "y".to_string(),
+                    ..Default::default()
+                })
+            ]
+        );
+        assert_eq!(
+            EventStream::new(futures::stream::iter(vec![Ok::<_, ()>(
+                "data: ab\ndata: cd\n\ndata: e\n\n"
+            )]))
+            .with_limits(Limits {
+                max_event_size: Some(4),
+                on_exceeded: LimitAction::Skip,
+                ..Default::default()
+            })
+            .collect::<Vec<_>>()
+            .await,
+            vec![
+                Err(EventStreamError::LimitExceeded(Limit::EventSize)),
+                Ok(Event {
+                    data: "e".to_string(),
+                    ..Default::default()
+                })
+            ]
+        );
+        let mut stream = EventStream::new(futures::stream::iter(vec![
+            Ok::<_, ()>("data: 0123456789"),
+            Ok::<_, ()>("abcdef"),
+            Ok::<_, ()>("\n\n"),
+        ]))
+        .with_limits(Limits {
+            max_buffered: Some(16),
+            ..Default::default()
+        });
+        assert_eq!(
+            stream.by_ref().collect::<Vec<_>>().await,
+            vec![Err(EventStreamError::LimitExceeded(Limit::Buffered))]
+        );
+        assert_eq!(stream.stream_end(), Some(StreamEnd::Aborted));
+
+        // Skipping an oversized line does not refuse the chunks after it
+        assert_eq!(
+            EventStream::new(futures::stream::iter(vec![
+                Ok::<_, ()>("data: a"),
+                Ok::<_, ()>("bc\n\n"),
+                Ok::<_, ()>("data: ok\n\n"),
+                Ok::<_, ()>("data: ok2\n\n"),
+            ]))
+            .with_limits(Limits {
+                max_buffered: Some(0),
+                on_exceeded: LimitAction::Skip,
+                ..Default::default()
+            })
+            .collect::<Vec<_>>()
+            .await,
+            vec![
+                Err(EventStreamError::LimitExceeded(Limit::Buffered)),
+                Ok(Event {
+                    data: "ok".to_string(),
+                    ..Default::default()
+                }),
+                Ok(Event {
+                    data: "ok2".to_string(),
+                    ..Default::default()
+                })
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn extended() {
+        assert_eq!(
+            EventStream::new(futures::stream::iter(vec![Ok::<_, ()>(
+                ": ping\nevent: add\nx-vendor: 42\nflag\ndata: 1\n\n:\nid: 9\n\nid: 9\n\nid\n\n"
+            )]))
+            .extended()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap(),
+            vec![
+                EventStreamItem::Comment(" ping".to_string()),
+                EventStreamItem::Field("x-vendor".to_string(), "42".to_string()),
+                EventStreamItem::Field("flag".to_string(), "".to_string()),
+                EventStreamItem::Event(Event {
+                    event: "add".to_string(),
+                    data: "1".to_string(),
+                    ..Default::default()
+                }),
+                EventStreamItem::Comment("".to_string()),
+                EventStreamItem::Id("9".to_string()),
+                EventStreamItem::Id("".to_string()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn initial_state() {
+        let mut stream = EventStream::new(futures::stream::iter(vec![Ok::<_, ()>(
+            "data: a\n\nid: 8\ndata: b\n\n",
+        )]))
+        .with_last_event_id("7")
+        .with_retry(Duration::from_secs(1));
+        assert_eq!(stream.last_event_id(), "7");
+        assert_eq!(stream.retry(), Some(Duration::from_secs(1)));
+        assert_eq!(
+            stream.by_ref().try_collect::<Vec<_>>().await.unwrap(),
+            vec![
+                Event {
+                    id: "7".to_string(),
+                    data: "a".to_string(),
+                    ..Default::default()
+                },
+                Event {
+                    id: "8".to_string(),
+                    data: "b".to_string(),
+                    ..Default::default()
+                }
+            ]
+        );
+        assert_eq!(stream.last_event_id(), "8");
+    }
+
+    #[tokio::test]
+    async fn retry() {
+        let mut stream = EventStream::new(futures::stream::iter(vec![Ok::<_, ()>(
+            "retry: 5000\n\nretry: x\nretry: +5\nretry: -5\ndata: a\n\n",
+        )]));
+        assert_eq!(stream.retry(), None);
+        assert_eq!(
+            stream.by_ref().try_collect::<Vec<_>>().await.unwrap(),
+            vec![Event {
+                data: "a".to_string(),
+                ..Default::default()
+            }]
+        );
+        assert_eq!(stream.retry(), Some(Duration::from_secs(5)));
+
+        let mut stream = EventStream::new(futures::stream::iter(vec![Ok::<_, ()>(
+            "retry: 10\n\ndata: a\nretry: +5\nretry: 20\nretry: 20\n\nretry: 10\n\n",
+        )]))
+        .with_retry(Duration::from_millis(10))
+        .extended();
+        assert_eq!(
+            stream.by_ref().try_collect::<Vec<_>>().await.unwrap(),
+            vec![
+                EventStreamItem::Retry(Duration::from_millis(20)),
+                EventStreamItem::Event(Event {
+                    data: "a".to_string(),
+                    retry: Some(Duration::from_millis(20)),
+                    ..Default::default()
+                }),
+                EventStreamItem::Retry(Duration::from_millis(10)),
+            ]
+        );
+        assert_eq!(stream.retry(), Some(Duration::from_millis(10)));
+    }
+
     #[tokio::test]
     async fn spec_examples() {
         assert_eq!(
@@ -0,0 +1,336 @@
+use crate::event::Event;
+use core::pin::Pin;
+use futures_core::stream::Stream;
+use futures_core::task::{Context, Poll, Waker};
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+/// What a [`Subscription`] does when it fell so far behind that the events it did not receive yet
+/// were evicted from the buffer of the [`SseHub`]
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub enum LagPolicy {
+    /// Skip the evicted events and resume at the oldest buffered event
+    #[default]
+    Drop,
+    /// End the subscription
+    Disconnect,
+    /// Skip all events but the latest one
+    Coalesce,
+}
+
+#[derive(Debug)]
+struct Shared {
+    buffer: VecDeque<Event>,
+    capacity: usize,
+    // The id of the first buffered event
+    first_id: u64,
+    lag_policy: LagPolicy,
+    wakers: BTreeMap<u64, Waker>,
+    next_key: u64,
+    is_closed: bool,
+}
+
+impl Shared {
+    fn next_id(&self) -> u64 {
+        self.first_id + self.buffer.len() as u64
+    }
+}
+
+/// A broadcast of events to any number of subscribers, with replay of missed events
+///
+/// Every published event gets the next of monotonically increasing ids, starting at 1. The latest
+/// `capacity` events are kept, so a client reconnecting with its `Last-Event-ID` gets the events it
+/// missed before the live ones. The same buffer holds the events a slow subscriber did not receive
+/// yet, and the [`LagPolicy`] decides what happens once they are evicted.
+///
+/// ```
+/// # use eventsource_stream::{Event, SseHub};
+/// # use futures::stream::StreamExt;
+/// # futures::executor::block_on(async {
+/// let hub = SseHub::new(128);
+/// let mut live = hub.subscribe("");
+/// hub.publish(Event {
+///     data: "hello".to_string(),
+///     ..Default::default()
+/// });
+/// assert_eq!(live.next().await.unwrap().id, "1");
+///
+/// // A client reconnecting with `Last-Event-ID: 0` gets the first event again
+/// let mut replay = hub.subscribe("0");
+/// assert_eq!(replay.next().await.unwrap().data, "hello");
+/// # });
+/// ```
+#[derive(Debug, Clone)]
+pub struct SseHub {
+    shared: Arc<Mutex<Shared>>,
+}
+
+impl SseHub {
+    /// Create a hub which keeps the latest `capacity` events
+    ///
+    /// # Panics
+    ///
+    /// If `capacity` is zero
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "SseHub capacity must be positive");
+        Self {
+            shared: Arc::new(Mutex::new(Shared {
+                buffer: VecDeque::with_capacity(capacity),
+                capacity,
+                first_id: 1,
+                lag_policy: LagPolicy::default(),
+                wakers: BTreeMap::new(),
+                next_key: 0,
+                is_closed: false,
+            })),
+        }
+    }
+
+    /// Set what subscribers do when they lag behind
+    pub fn with_lag_policy(self, lag_policy: LagPolicy) -> Self {
+        self.lock().lag_policy = lag_policy;
+        self
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Shared> {
+        lock(&self.shared)
+    }
+
+    /// Send `event` to all subscribers, returning the id it was assigned. The `id` of the event is
+    /// replaced.
+    pub fn publish(&self, mut event: Event) -> u64 {
+        let mut shared = self.lock();
+        let id = shared.next_id();
+        event.id = id.to_string();
+        if shared.buffer.len() == shared.capacity {
+            shared.buffer.pop_front();
+            shared.first_id += 1;
+        }
+        shared.buffer.push_back(event);
+        wake_all(shared);
+        id
+    }
+
+    /// Receive the events published after the one with id `last_event_id`, starting with the
+    /// buffered ones. With an empty or unknown id only new events are received. If some of the
+    /// missed events were evicted already, the replay starts at the oldest buffered event, whatever
+    /// the [`LagPolicy`].
+    pub fn subscribe(&self, last_event_id: &str) -> Subscription {
+        let mut shared = self.lock();
+        let next_id = shared.next_id();
+        let cursor = match last_event_id.parse::<u64>() {
+            Ok(id) if id < next_id => (id + 1).max(shared.first_id),
+            _ => next_id,
+        };
+        let key = shared.next_key;
+        shared.next_key += 1;
+        Subscription {
+            shared: self.shared.clone(),
+            cursor,
+            key,
+            is_done: false,
+        }
+    }
+
+    /// Get the id of the latest published event, if any
+    pub fn last_event_id(&self) -> Option<u64> {
+        self.lock().next_id().checked_sub(1).filter(|id| *id > 0)
+    }
+
+    /// End all subscriptions once they received the published events
+    pub fn close(&self) {
+        let mut shared = self.lock();
+        shared.is_closed = true;
+        wake_all(shared);
+    }
+}
+
+fn lock(shared: &Mutex<Shared>) -> MutexGuard<'_, Shared> {
+    shared.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn wake_all(mut shared: MutexGuard<'_, Shared>) {
+    let wakers = core::mem::take(&mut shared.wakers);
+    drop(shared);
+    for waker in wakers.into_values() {
+        waker.wake();
+    }
+}
+
+/// A Stream of the events of an [`SseHub`]
+#[derive(Debug)]
+pub struct Subscription {
+    shared: Arc<Mutex<Shared>>,
+    // The id of the next event to receive
+    cursor: u64,
+    key: u64,
+    is_done: bool,
+}
+
+impl Subscription {
+    /// Get the id of the next event to receive
+    pub fn next_id(&self) -> u64 {
+        self.cursor
+    }
+}
+
+impl Stream for Subscription {
+    type Item = Event;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        if this.is_done {
+            return Poll::Ready(None);
+        }
+        let mut shared = lock(&this.shared);
+        let next_id = shared.next_id();
+        if this.cursor < shared.first_id {
+            match shared.lag_policy {
+                LagPolicy::Drop => this.cursor = shared.first_id,
+                LagPolicy::Disconnect => {
+                    this.is_done = true;
+                    shared.wakers.remove(&this.key);
+                    return Poll::Ready(None);
+                }
+                LagPolicy::Coalesce => this.cursor = next_id - 1,
+            }
+        }
+        if this.cursor < next_id {
+            let event = shared.buffer[(this.cursor - shared.first_id) as usize].clone();
+            this.cursor += 1;
+            return Poll::Ready(Some(event));
+        }
+        if shared.is_closed {
+            this.is_done = true;
+            shared.wakers.remove(&this.key);
+            return Poll::Ready(None);
+        }
+        shared.wakers.insert(this.key, cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        lock(&self.shared).wakers.remove(&self.key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::prelude::*;
+
+    fn publish(hub: &SseHub, data: &str) -> u64 {
+        hub.publish(Event {
+            data: data.to_string(),
+            id: "ignored".to_string(),
+            ..Default::default()
+        })
+    }
+
+    fn received(subscription: Subscription) -> Vec<(String, String)> {
+        futures::executor::block_on(
+            subscription
+                .map(|event| (event.id, event.data))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    fn pairs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(id, data)| (id.to_string(), data.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn replay() {
+        let hub = SseHub::new(3);
+        assert_eq!(hub.last_event_id(), None);
+        let live = hub.subscribe("");
+        let from_start = hub.subscribe("0");
+        for data in ["a", "b", "c", "d"] {
+            publish(&hub, data);
+        }
+        assert_eq!(hub.last_event_id(), Some(4));
+        let resumed = hub.subscribe("2");
+        let unknown = hub.subscribe("not a number");
+        let future = hub.subscribe("9");
+        publish(&hub, "e");
+        hub.close();
+
+        assert_eq!(
+            received(resumed),
+            pairs(&[("3", "c"), ("4", "d"), ("5", "e")])
+        );
+        assert_eq!(received(unknown), pairs(&[("5", "e")]));
+        assert_eq!(received(future), pairs(&[("5", "e")]));
+        // The live and replaying subscriptions lag behind the buffer of 3 events
+        assert_eq!(received(live), pairs(&[("3", "c"), ("4", "d"), ("5", "e")]));
+        assert_eq!(
+            received(from_start),
+            pairs(&[("3", "c"), ("4", "d"), ("5", "e")])
+        );
+    }
+
+    #[test]
+    fn lag_policy() {
+        for (policy, expected) in [
+            (
+                LagPolicy::Drop,
+                pairs(&[("2", "b"), ("3", "c"), ("4", "d")]),
+            ),
+            (LagPolicy::Disconnect, pairs(&[])),
+            (LagPolicy::Coalesce, pairs(&[("4", "d")])),
+        ] {
+            let hub = SseHub::new(3).with_lag_policy(policy);
+            let subscription = hub.subscribe("");
+            for data in ["a", "b", "c", "d"] {
+                publish(&hub, data);
+            }
+            hub.close();
+            assert_eq!(received(subscription), expected, "{:?}", policy);
+        }
+    }
+
+    #[test]
+    fn replay_evicted() {
+        for policy in [LagPolicy::Drop, LagPolicy::Disconnect, LagPolicy::Coalesce] {
+            let hub = SseHub::new(2).with_lag_policy(policy);
+            for data in ["a", "b", "c"] {
+                publish(&hub, data);
+            }
+            let subscription = hub.subscribe("0");
+            assert_eq!(subscription.next_id(), 2);
+            hub.close();
+            assert_eq!(
+                received(subscription),
+                pairs(&[("2", "b"), ("3", "c")]),
+                "{:?}",
+                policy
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn wake_subscribers() {
+        let hub = SseHub::new(8);
+        let subscriptions = (0..3).map(|_| hub.subscribe("")).collect::<Vec<_>>();
+        let tasks = subscriptions
+            .into_iter()
+            .map(|subscription| {
+                tokio::spawn(subscription.map(|event| event.data).collect::<Vec<_>>())
+            })
+            .collect::<Vec<_>>();
+        tokio::task::yield_now().await;
+        publish(&hub, "a");
+        tokio::task::yield_now().await;
+        publish(&hub, "b");
+        hub.close();
+        for task in tasks {
+            assert_eq!(task.await.unwrap(), vec!["a", "b"]);
+        }
+        assert!(hub.lock().wakers.is_empty());
+    }
+}
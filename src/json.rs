@@ -0,0 +1,318 @@
+use crate::event::Event;
+use crate::event_stream::EventStream;
+use core::fmt;
+use core::marker::PhantomData;
+use core::pin::Pin;
+use core::time::Duration;
+use futures_core::ready;
+use futures_core::stream::Stream;
+use futures_core::task::{Context, Poll};
+use pin_project::pin_project;
+use serde::de::{Deserialize, DeserializeOwned};
+
+impl Event {
+    /// Deserialize the data of the event from JSON
+    pub fn json<'a, T: Deserialize<'a>>(&'a self) -> serde_json::Result<T> {
+        serde_json::from_str(&self.data)
+    }
+}
+
+/// An event whose data was deserialized from JSON, see [`EventStream::json`]
+#[derive(Default, Debug, Clone, Eq, PartialEq)]
+pub struct TypedEvent<T> {
+    /// The event name if given
+    pub event: String,
+    /// The deserialized event data
+    pub data: T,
+    /// The event id if given
+    pub id: String,
+    /// Retry duration if given
+    pub retry: Option<Duration>,
+}
+
+/// Error yielded by a [`JsonEventStream`]
+#[derive(Debug)]
+pub enum JsonError<E> {
+    /// The underlying event stream failed
+    Stream(E),
+    /// The data of the event is not valid JSON for the requested type. The stream keeps going
+    /// after this error.
+    Json(Event, serde_json::Error),
+}
+
+impl<E> fmt::Display for JsonError<E>
+where
+    E: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Stream(err) => err.fmt(f),
+            Self::Json(_, err) => f.write_fmt(format_args!("JSON error: {}", err)),
+        }
+    }
+}
+
+impl<E> std::error::Error for JsonError<E> where E: fmt::Display + fmt::Debug {}
+
+/// A type which events are converted to depending on their `event` field, usually an enum
+/// implementing it with `#[derive(SseEvent)]` from the `derive` feature
+pub trait SseEvent: Sized {
+    /// Convert `event`, or return `None` if its type is not handled
+    fn from_event(event: &Event) -> Option<serde_json::Result<Self>>;
+}
+
+impl<S> EventStream<S> {
+    /// Deserialize the data of every event from JSON as `T`
+    pub fn json<T>(self) -> JsonEventStream<Self, T> {
+        JsonEventStream::new(self)
+    }
+
+    /// Convert every event to `T` by its type, see [`SseEventStream`]
+    pub fn typed<T: SseEvent>(self) -> SseEventStream<Self, T> {
+        SseEventStream::new(self)
+    }
+}
+
+/// A Stream of events with JSON data deserialized as `T`
+///
+/// It wraps any stream of `Result<Event, E>`, such as an [`EventStream`] or a
+/// [`crate::ReconnectingEventStream`].
+#[pin_project]
+pub struct JsonEventStream<S, T> {
+    #[pin]
+    stream: S,
+    event_type: Option<String>,
+    data: PhantomData<fn() -> T>,
+}
+
+impl<S, T> JsonEventStream<S, T> {
+    /// Deserialize the data of every event of `stream` from JSON as `T`
+    pub fn new(stream: S) -> Self {
+        Self {
+            stream,
+            event_type: None,
+            data: PhantomData,
+        }
+    }
+
+    /// Only deserialize events of type `event_type` and skip all others. Events without an
+    /// `event` field have the type `message`.
+    pub fn with_event_type(mut self, event_type: impl Into<String>) -> Self {
+        self.event_type = Some(event_type.into());
+        self
+    }
+
+    /// Get a reference to the underlying stream
+    pub fn get_ref(&self) -> &S {
+        &self.stream
+    }
+}
+
+impl<S, T, E> Stream for JsonEventStream<S, T>
+where
+    S: Stream<Item = Result<Event, E>>,
+    T: DeserializeOwned,
+{
+    type Item = Result<TypedEvent<T>, JsonError<E>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        loop {
+            let event = match ready!(this.stream.as_mut().poll_next(cx)) {
+                Some(Ok(event)) => event,
+                Some(Err(err)) => return Poll::Ready(Some(Err(JsonError::Stream(err)))),
+                None => return Poll::Ready(None),
+            };
+            if let Some(event_type) = this.event_type {
+                let is_match = if event.event.is_empty() {
+                    event_type == "message"
+                } else {
+                    event.event == *event_type
+                };
+                if !is_match {
+                    continue;
+                }
+            }
+            return Poll::Ready(Some(match serde_json::from_str(&event.data) {
+                Ok(data) => Ok(TypedEvent {
+                    event: event.event,
+                    data,
+                    id: event.id,
+                    retry: event.retry,
+                }),
+                Err(err) => Err(JsonError::Json(event, err)),
+            }));
+        }
+    }
+}
+
+/// A Stream of events converted to an [`SseEvent`]
+///
+/// Events whose type is not handled by `T` are skipped. Like [`JsonEventStream`], it wraps any
+/// stream of `Result<Event, E>`.
+#[pin_project]
+pub struct SseEventStream<S, T> {
+    #[pin]
+    stream: S,
+    data: PhantomData<fn() -> T>,
+}
+
+impl<S, T> SseEventStream<S, T> {
+    /// Convert every event of `stream` to `T`
+    pub fn new(stream: S) -> Self {
+        Self {
+            stream,
+            data: PhantomData,
+        }
+    }
+
+    /// Get a reference to the underlying stream
+    pub fn get_ref(&self) -> &S {
+        &self.stream
+    }
+}
+
+impl<S, T, E> Stream for SseEventStream<S, T>
+where
+    S: Stream<Item = Result<Event, E>>,
+    T: SseEvent,
+{
+    type Item = Result<T, JsonError<E>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        loop {
+            let event = match ready!(this.stream.as_mut().poll_next(cx)) {
+                Some(Ok(event)) => event,
+                Some(Err(err)) => return Poll::Ready(Some(Err(JsonError::Stream(err)))),
+                None => return Poll::Ready(None),
+            };
+            match T::from_event(&event) {
+                Some(Ok(item)) => return Poll::Ready(Some(Ok(item))),
+                Some(Err(err)) => return Poll::Ready(Some(Err(JsonError::Json(event, err)))),
+                None => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Eventsource;
+    use futures::prelude::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Price<'a> {
+        symbol: &'a str,
+        price: u32,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Tick {
+        price: u32,
+    }
+
+    #[test]
+    fn event_json() {
+        let event = Event {
+            data: r#"{"symbol": "ABC", "price": 42}"#.to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            event.json::<Price>().unwrap(),
+            Price {
+                symbol: "ABC",
+                price: 42
+            }
+        );
+        assert!(event.json::<u32>().is_err());
+    }
+
+    #[tokio::test]
+    async fn json_stream() {
+        let input = concat!(
+            "event: tick\ndata: {\"price\": 1}\n\n",
+            "event: tick\ndata: not json\n\n",
+            "event: status\ndata: {\"up\": true}\n\n",
+            "id: 2\ndata: {\"price\": 2}\n\n",
+        );
+        let results = stream::iter(vec![Ok::<_, ()>(input)])
+            .eventsource()
+            .json::<Tick>()
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(results.len(), 4);
+        assert_eq!(
+            results[0].as_ref().unwrap(),
+            &TypedEvent {
+                event: "tick".to_string(),
+                data: Tick { price: 1 },
+                id: "".to_string(),
+                retry: None
+            }
+        );
+        assert!(matches!(&results[1], Err(JsonError::Json(event, _)) if event.data == "not json"));
+        assert!(matches!(results[2], Err(JsonError::Json(..))));
+        assert_eq!(results[3].as_ref().unwrap().data, Tick { price: 2 });
+
+        let events = stream::iter(vec![Ok::<_, ()>(input)])
+            .eventsource()
+            .json::<Tick>()
+            .with_event_type("message")
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(
+            events,
+            vec![TypedEvent {
+                event: "".to_string(),
+                data: Tick { price: 2 },
+                id: "2".to_string(),
+                retry: None
+            }]
+        );
+    }
+
+    #[cfg(feature = "derive")]
+    #[tokio::test]
+    async fn derive() {
+        #[derive(Debug, PartialEq, crate::SseEvent)]
+        enum Update {
+            #[sse(event = "tick")]
+            Tick(Tick),
+            #[sse(event = "remove")]
+            Remove(u64),
+            #[sse(event = "message")]
+            Message(String),
+            Ping,
+        }
+
+        let input = concat!(
+            "event: tick\ndata: {\"price\": 1}\n\n",
+            "event: status\ndata: {\"up\": true}\n\n",
+            "event: remove\ndata: x\n\n",
+            "event: remove\ndata: 7\n\n",
+            "data: \"hello\"\n\n",
+            "event: Ping\ndata: -\n\n",
+        );
+        let results = stream::iter(vec![Ok::<_, ()>(input)])
+            .eventsource()
+            .typed::<Update>()
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(results.len(), 5);
+        assert_eq!(
+            results[0].as_ref().unwrap(),
+            &Update::Tick(Tick { price: 1 })
+        );
+        assert!(matches!(&results[1], Err(JsonError::Json(event, _)) if event.event == "remove"));
+        assert_eq!(results[2].as_ref().unwrap(), &Update::Remove(7));
+        assert_eq!(
+            results[3].as_ref().unwrap(),
+            &Update::Message("hello".to_string())
+        );
+        assert_eq!(results[4].as_ref().unwrap(), &Update::Ping);
+    }
+}
@@ -0,0 +1,438 @@
+use crate::body::BodyStream;
+use crate::encoder::{encode_comment, encode_event};
+use crate::event::{Event, EventStreamItem};
+use crate::event_stream::{EventStream, EventStreamError, ExtendedEventStream};
+use crate::mime;
+use bytes::{Buf, Bytes};
+use core::convert::Infallible;
+use core::fmt::Write;
+use core::future::Future;
+use core::pin::Pin;
+use core::time::Duration;
+use futures_core::ready;
+use futures_core::stream::Stream;
+use futures_core::task::{Context, Poll};
+use http::header::{CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_TYPE};
+use http::{HeaderMap, Response};
+use http_body::{Body, Frame};
+use pin_project::pin_project;
+use tower_layer::Layer;
+use tower_service::Service;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// A [`Layer`] rewriting the events of `text/event-stream` responses
+///
+/// The body of every uncompressed `text/event-stream` response is parsed as it streams in, every
+/// event is passed to the transform, and the events it returns are encoded again. Returning `None`
+/// drops the event. Comments, such as keep-alives, and changes of the reconnection time are
+/// forwarded unchanged, and so are changes of the last event ID by blocks without data or by
+/// dropped events, so clients reconnect with the same `Last-Event-ID`. Fields which are not part of the spec are dropped.
+/// Other responses are passed through.
+///
+/// ```
+/// # use eventsource_stream::{Event, SseLayer};
+/// let layer = SseLayer::new(|event: Event| {
+///     if event.event == "internal" {
+///         None
+///     } else {
+///         Some(Event {
+///             data: event.data.replace("secret", "******"),
+///             ..event
+///         })
+///     }
+/// });
+/// ```
+#[derive(Debug, Clone)]
+pub struct SseLayer<F> {
+    transform: F,
+}
+
+impl<F> SseLayer<F> {
+    /// Rewrite events with `transform`, which is cloned for every response
+    pub fn new(transform: F) -> Self {
+        Self { transform }
+    }
+}
+
+impl<S, F> Layer<S> for SseLayer<F>
+where
+    F: Clone,
+{
+    type Service = SseService<S, F>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        SseService {
+            inner,
+            transform: self.transform.clone(),
+        }
+    }
+}
+
+/// The [`Service`] of an [`SseLayer`]
+#[derive(Debug, Clone)]
+pub struct SseService<S, F> {
+    inner: S,
+    transform: F,
+}
+
+impl<S, F, Req, B> Service<Req> for SseService<S, F>
+where
+    S: Service<Req, Response = Response<B>>,
+    F: FnMut(Event) -> Option<Event> + Clone,
+    B: Body,
+    B::Error: Into<BoxError>,
+{
+    type Response = Response<RewriteBody<B, F>>;
+    type Error = S::Error;
+    type Future = ResponseFuture<S::Future, F>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Req) -> Self::Future {
+        ResponseFuture {
+            future: self.inner.call(request),
+            transform: Some(self.transform.clone()),
+        }
+    }
+}
+
+/// The response future of an [`SseService`]
+#[pin_project]
+pub struct ResponseFuture<Fut, F> {
+    #[pin]
+    future: Fut,
+    transform: Option<F>,
+}
+
+fn is_event_stream(headers: &HeaderMap) -> bool {
+    let is_encoded = headers
+        .get(CONTENT_ENCODING)
+        .map(|encoding| encoding != "identity")
+        .unwrap_or(false);
+    !is_encoded
+        && headers
+            .get(CONTENT_TYPE)
+            .map(|content_type| mime::is_event_stream(content_type.as_bytes()))
+            .unwrap_or(false)
+}
+
+fn write_id(buffer: &mut String, id: &str) {
+    if id.is_empty() {
+        buffer.push_str("id\n\n");
+    } else {
+        let _ = write!(buffer, "id: {}\n\n", id);
+    }
+}
+
+impl<Fut, F, B, E> Future for ResponseFuture<Fut, F>
+where
+    Fut: Future<Output = Result<Response<B>, E>>,
+{
+    type Output = Result<Response<RewriteBody<B, F>>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let response = ready!(this.future.poll(cx))?;
+        let transform = this
+            .transform
+            .take()
+            .expect("ResponseFuture polled after completion");
+        if !is_event_stream(response.headers()) {
+            return Poll::Ready(Ok(response.map(|body| RewriteBody {
+                inner: Inner::Passthrough { body },
+            })));
+        }
+        let (mut parts, body) = response.into_parts();
+        parts.headers.remove(CONTENT_LENGTH);
+        let body = RewriteBody {
+            inner: Inner::Rewrite {
+                stream: EventStream::from_body(body).extended(),
+                transform,
+                buffer: String::new(),
+                block_retry: None,
+                last_event_id: String::new(),
+                is_done: false,
+            },
+        };
+        Poll::Ready(Ok(Response::from_parts(parts, body)))
+    }
+}
+
+/// The body of a response of an [`SseService`]
+#[pin_project]
+pub struct RewriteBody<B, F> {
+    #[pin]
+    inner: Inner<B, F>,
+}
+
+#[allow(clippy::large_enum_variant)]
+#[pin_project(project = InnerProj)]
+enum Inner<B, F> {
+    Passthrough {
+        #[pin]
+        body: B,
+    },
+    Rewrite {
+        #[pin]
+        stream: ExtendedEventStream<BodyStream<B>>,
+        transform: F,
+        buffer: String,
+        // The `retry` field already forwarded for the next event
+        block_retry: Option<Duration>,
+        // The last event ID of the client, as set by the forwarded events
+        last_event_id: String,
+        is_done: bool,
+    },
+}
+
+fn box_error<E: Into<BoxError>>(err: EventStreamError<E>) -> BoxError {
+    match err {
+        EventStreamError::Transport(err) => err.into(),
+        EventStreamError::Utf8(err) => Box::new(EventStreamError::<Infallible>::Utf8(err)),
+        EventStreamError::Parser(err) => Box::new(EventStreamError::<Infallible>::Parser(err)),
+        EventStreamError::LimitExceeded(limit) => {
+            Box::new(EventStreamError::<Infallible>::LimitExceeded(limit))
+        }
+    }
+}
+
+impl<B, F> Body for RewriteBody<B, F>
+where
+    B: Body,
+    B::Error: Into<BoxError>,
+    F: FnMut(Event) -> Option<Event>,
+{
+    type Data = Bytes;
+    type Error = BoxError;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let (mut stream, transform, buffer, block_retry, last_event_id, is_done) =
+            match self.project().inner.project() {
+                InnerProj::Passthrough { body } => {
+                    return body.poll_frame(cx).map(|frame| {
+                        frame.map(|frame| {
+                            frame
+                                .map(|frame| {
+                                    frame.map_data(|mut data| data.copy_to_bytes(data.remaining()))
+                                })
+                                .map_err(Into::into)
+                        })
+                    })
+                }
+                InnerProj::Rewrite {
+                    stream,
+                    transform,
+                    buffer,
+                    block_retry,
+                    last_event_id,
+                    is_done,
+                } => (
+                    stream,
+                    transform,
+                    buffer,
+                    block_retry,
+                    last_event_id,
+                    is_done,
+                ),
+            };
+        if *is_done {
+            return Poll::Ready(None);
+        }
+        loop {
+            buffer.clear();
+            match ready!(stream.as_mut().poll_next(cx)) {
+                Some(Ok(EventStreamItem::Event(event))) => {
+                    let forwarded = block_retry.take();
+                    let changed_id = (event.id != *last_event_id).then(|| event.id.clone());
+                    let mut event = match (transform(event), changed_id) {
+                        (Some(event), _) => event,
+                        (None, Some(id)) => {
+                            write_id(buffer, &id);
+                            *last_event_id = id;
+                            return Poll::Ready(Some(Ok(Frame::data(Bytes::copy_from_slice(
+                                buffer.as_bytes(),
+                            )))));
+                        }
+                        (None, None) => continue,
+                    };
+                    if event.retry == forwarded {
+                        event.retry = None;
+                    }
+                    // An empty id is not encoded, which would keep the previous one
+                    if event.id.is_empty() && !last_event_id.is_empty() {
+                        buffer.push_str("id\n");
+                    }
+                    if let Err(err) = encode_event(&event, buffer) {
+                        return Poll::Ready(Some(Err(Box::new(err))));
+                    }
+                    last_event_id.clone_from(&event.id);
+                }
+                Some(Ok(EventStreamItem::Comment(comment))) => encode_comment(&comment, buffer),
+                Some(Ok(EventStreamItem::Retry(retry))) => {
+                    *block_retry = Some(retry);
+                    let _ = writeln!(buffer, "retry: {}", retry.as_millis());
+                }
+                Some(Ok(EventStreamItem::Id(id))) => {
+                    *block_retry = None;
+                    if id == *last_event_id {
+                        continue;
+                    }
+                    write_id(buffer, &id);
+                    *last_event_id = id;
+                }
+                Some(Ok(EventStreamItem::Field(..))) => continue,
+                Some(Err(err)) => return Poll::Ready(Some(Err(box_error(err)))),
+                None => {
+                    *is_done = true;
+                    let trailers = stream.get_ref().get_ref().trailers().cloned();
+                    return Poll::Ready(trailers.map(|trailers| Ok(Frame::trailers(trailers))));
+                }
+            }
+            return Poll::Ready(Some(Ok(Frame::data(Bytes::copy_from_slice(
+                buffer.as_bytes(),
+            )))));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::prelude::*;
+    use http::HeaderValue;
+    use http_body_util::{BodyExt, Full, StreamBody};
+    use tower::{service_fn, ServiceExt};
+
+    fn redact(event: Event) -> Option<Event> {
+        if event.event == "internal" {
+            return None;
+        }
+        Some(Event {
+            event: event.event.replace("old", "new"),
+            data: event.data.replace("secret", "******"),
+            ..event
+        })
+    }
+
+    #[tokio::test]
+    async fn rewrite_events() {
+        let service = SseLayer::new(redact).layer(service_fn(|_: ()| {
+            let mut trailers = HeaderMap::new();
+            trailers.insert("x-checksum", HeaderValue::from_static("abc"));
+            let frames = vec![
+                Ok::<_, Infallible>(Frame::data(Bytes::from_static(
+                    b": ping\n\nevent: old\ndata: the secret\n\nevent: internal\ndata: x",
+                ))),
+                Ok(Frame::data(Bytes::from_static(
+                    b"\n\nretry: 100\nid: 1\ndata: y\n\nretry: 200\n\n",
+                ))),
+                Ok(Frame::trailers(trailers)),
+            ];
+            async move {
+                let mut response = Response::new(StreamBody::new(stream::iter(frames)));
+                response.headers_mut().insert(
+                    CONTENT_TYPE,
+                    HeaderValue::from_static("text/event-stream; charset=utf-8"),
+                );
+                response
+                    .headers_mut()
+                    .insert(CONTENT_LENGTH, HeaderValue::from_static("80"));
+                Ok::<_, Infallible>(response)
+            }
+        }));
+        let response = service.oneshot(()).await.unwrap();
+        assert!(response.headers().get(CONTENT_LENGTH).is_none());
+        let body = response.into_body().collect().await.unwrap();
+        assert_eq!(body.trailers().unwrap()["x-checksum"], "abc");
+        assert_eq!(
+            &body.to_bytes()[..],
+            &b": ping\nevent: new\ndata: the ******\n\nretry: 100\nid: 1\ndata: y\n\nretry: 200\n"
+                [..]
+        );
+    }
+
+    #[tokio::test]
+    async fn forward_last_event_id() {
+        let service = SseLayer::new(Some).layer(service_fn(|_: ()| async {
+            let mut response = Response::new(Full::new(Bytes::from_static(
+                b"id: 1\ndata: a\n\nid: 9\n\nid\ndata: b\n\nid: 3\n\nid\n\nx-vendor: 1\ndata: c\n\n",
+            )));
+            response
+                .headers_mut()
+                .insert(CONTENT_TYPE, HeaderValue::from_static("text/event-stream"));
+            Ok::<_, Infallible>(response)
+        }));
+        let body = service
+            .oneshot(())
+            .await
+            .unwrap()
+            .into_body()
+            .collect()
+            .await
+            .unwrap()
+            .to_bytes();
+        assert_eq!(
+            &body[..],
+            &b"id: 1\ndata: a\n\nid: 9\n\nid\ndata: b\n\nid: 3\n\nid\n\ndata: c\n\n"[..]
+        );
+    }
+
+    #[tokio::test]
+    async fn forward_id_of_dropped_events() {
+        let service = SseLayer::new(redact).layer(service_fn(|_: ()| async {
+            let mut response = Response::new(Full::new(Bytes::from_static(
+                concat!(
+                    "id: 1\ndata: a\n\n",
+                    "id: 2\nevent: internal\ndata: x\n\n",
+                    "event: internal\ndata: y\n\n",
+                    "data: b\n\n",
+                    "id\nevent: internal\ndata: z\n\n",
+                    "data: c\n\n",
+                )
+                .as_bytes(),
+            )));
+            response
+                .headers_mut()
+                .insert(CONTENT_TYPE, HeaderValue::from_static("text/event-stream"));
+            Ok::<_, Infallible>(response)
+        }));
+        let body = service
+            .oneshot(())
+            .await
+            .unwrap()
+            .into_body()
+            .collect()
+            .await
+            .unwrap()
+            .to_bytes();
+        assert_eq!(
+            &body[..],
+            &b"id: 1\ndata: a\n\nid: 2\n\nid: 2\ndata: b\n\nid\n\ndata: c\n\n"[..]
+        );
+    }
+
+    #[tokio::test]
+    async fn passthrough() {
+        let service = SseLayer::new(redact).layer(service_fn(|_: ()| async {
+            Ok::<_, Infallible>(Response::new(Full::new(Bytes::from_static(
+                b"event: internal\ndata: secret\n\n",
+            ))))
+        }));
+        let body = service
+            .oneshot(())
+            .await
+            .unwrap()
+            .into_body()
+            .collect()
+            .await
+            .unwrap()
+            .to_bytes();
+        assert_eq!(&body[..], b"event: internal\ndata: secret\n\n");
+    }
+}
@@ -30,12 +30,92 @@
 #[cfg(not(feature = "std"))]
 extern crate alloc;
 
+// Lets the derive macro refer to this crate by name from within its own tests
+#[cfg(feature = "derive")]
+extern crate self as eventsource_stream;
+
+#[cfg(feature = "axum")]
+mod axum;
+#[cfg(feature = "http-body")]
+mod body;
+mod builder;
+#[cfg(feature = "bytes")]
+mod bytes_event;
+#[cfg(feature = "tokio-util")]
+mod codec;
+mod encoder;
 mod event;
+mod event_iter;
+#[cfg(feature = "sink")]
+mod event_sink;
 mod event_stream;
+#[cfg(feature = "std")]
+mod hub;
+#[cfg(feature = "serde_json")]
+mod json;
+#[cfg(feature = "tower")]
+mod layer;
+mod limits;
+#[cfg(any(feature = "reqwest", feature = "tower"))]
+mod mime;
 mod parser;
+mod push_parser;
+#[cfg(any(feature = "tokio", feature = "futures-io"))]
+mod reader;
+#[cfg(feature = "std")]
+mod reconnect;
+#[cfg(feature = "reqwest")]
+mod request;
+mod router;
 mod traits;
-mod utf8_stream;
+mod utf8;
 
-pub use event::Event;
-pub use event_stream::{EventStream, EventStreamError};
+#[cfg(feature = "axum")]
+pub use axum::{KeepAlive, Sse};
+#[cfg(feature = "http-body")]
+pub use body::{BodyStream, EventBody};
+#[cfg(feature = "bytes")]
+pub use bytes_event::{BytesEvent, BytesEventStream, BytesParser};
+#[cfg(feature = "tokio-util")]
+pub use codec::{SseCodec, SseCodecError};
+pub use encoder::{encode_comment, encode_event, EncodeError};
+pub use event::{Event, EventRef, EventStreamItem};
+pub use event_iter::EventIter;
+#[cfg(feature = "std")]
+pub use event_iter::ReadChunks;
+#[cfg(feature = "sink")]
+pub use event_sink::{EventSink, EventSinkError, SinkWriter};
+pub use event_stream::{EventStream, EventStreamError, ExtendedEventStream};
+#[cfg(feature = "derive")]
+pub use eventsource_stream_derive::SseEvent;
+#[cfg(feature = "std")]
+pub use hub::{LagPolicy, SseHub, Subscription};
+#[cfg(feature = "serde_json")]
+pub use json::{JsonError, JsonEventStream, SseEvent, SseEventStream, TypedEvent};
+#[cfg(feature = "tower")]
+pub use layer::{ResponseFuture, RewriteBody, SseLayer, SseService};
+pub use limits::{Limit, LimitAction, Limits};
+pub use push_parser::{Parser, StreamEnd};
+#[cfg(feature = "futures-io")]
+pub use reader::AsyncReadChunks;
+#[cfg(feature = "tokio")]
+pub use reader::TokioReadChunks;
+#[cfg(feature = "std")]
+pub use reconnect::{ReconnectError, ReconnectOptions, ReconnectingEventStream};
+#[cfg(feature = "reqwest")]
+pub use request::{EventsourceRequest, RequestBuilderExt, RequestError, ResponseBytes};
+pub use router::EventRouter;
+#[cfg(feature = "std")]
+pub use router::RouteStream;
+#[cfg(feature = "futures-io")]
+pub use traits::AsyncReadEventsource;
 pub use traits::Eventsource;
+#[cfg(feature = "tokio")]
+pub use traits::TokioEventsource;
+pub use utf8::Utf8Policy;
+
+#[cfg(feature = "derive")]
+#[doc(hidden)]
+pub mod __private {
+    pub use serde_json;
+}
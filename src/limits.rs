@@ -0,0 +1,61 @@
+use core::fmt;
+
+/// Size limits applied while parsing an event stream. Every limit is disabled by default.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct Limits {
+    /// Maximum length of a single line in bytes, excluding the line terminator
+    pub max_line_length: Option<usize>,
+    /// Maximum size of the data buffer of an event in bytes
+    pub max_event_size: Option<usize>,
+    /// Maximum number of fields in a single event
+    pub max_fields: Option<usize>,
+    /// Maximum number of bytes received but not parsed yet. Once the complete lines of the
+    /// input were parsed, the incomplete line left is checked like by `max_line_length`, so a
+    /// large chunk of small events is accepted. [`crate::Parser::feed`] refuses a chunk while
+    /// more bytes than this are waiting to be parsed, which bounds the input of a parser whose
+    /// items are not pulled.
+    pub max_buffered: Option<usize>,
+    /// What to do when one of the limits is exceeded
+    pub on_exceeded: LimitAction,
+}
+
+impl Limits {
+    #[inline]
+    pub(crate) fn exceeds(limit: Option<usize>, size: usize) -> bool {
+        matches!(limit, Some(limit) if size > limit)
+    }
+}
+
+/// What an [`crate::EventStream`] does after a limit was exceeded
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub enum LimitAction {
+    /// Return the error and end the stream
+    #[default]
+    Fail,
+    /// Return the error, drop the oversized event and resume at the next blank line
+    Skip,
+}
+
+/// A limit which can be exceeded, see [`Limits`]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Limit {
+    /// [`Limits::max_line_length`]
+    LineLength,
+    /// [`Limits::max_event_size`]
+    EventSize,
+    /// [`Limits::max_fields`]
+    Fields,
+    /// [`Limits::max_buffered`]
+    Buffered,
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LineLength => f.write_str("maximum line length"),
+            Self::EventSize => f.write_str("maximum event size"),
+            Self::Fields => f.write_str("maximum number of fields"),
+            Self::Buffered => f.write_str("maximum buffered bytes"),
+        }
+    }
+}
@@ -0,0 +1,27 @@
+/// The media type of an Eventsource
+pub(crate) const EVENT_STREAM: &str = "text/event-stream";
+
+/// Check whether the value of a `Content-Type` header is `text/event-stream`, ignoring its
+/// parameters and the case
+pub(crate) fn is_event_stream(content_type: &[u8]) -> bool {
+    core::str::from_utf8(content_type)
+        .ok()
+        .and_then(|content_type| content_type.split(';').next())
+        .map(|mime| mime.trim().eq_ignore_ascii_case(EVENT_STREAM))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn content_type() {
+        assert!(is_event_stream(b"text/event-stream"));
+        assert!(is_event_stream(b"Text/Event-Stream ; charset=utf-8"));
+        assert!(!is_event_stream(b"text/event-streams"));
+        assert!(!is_event_stream(b"application/json"));
+        assert!(!is_event_stream(b"\xfftext/event-stream"));
+        assert!(!is_event_stream(b""));
+    }
+}
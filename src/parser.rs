@@ -50,28 +50,23 @@ pub fn is_colon(c: char) -> bool {
     c == '\u{003A}'
 }
 
-#[inline]
-pub fn is_bom(c: char) -> bool {
-    c == '\u{feff}'
-}
-
 #[inline]
 pub fn is_name_char(c: char) -> bool {
-    match c {
+    matches!(
+        c,
         '\u{0000}'..='\u{0009}'
-        | '\u{000B}'..='\u{000C}'
-        | '\u{000E}'..='\u{0039}'
-        | '\u{003B}'..='\u{10FFFF}' => true,
-        _ => false,
-    }
+            | '\u{000B}'..='\u{000C}'
+            | '\u{000E}'..='\u{0039}'
+            | '\u{003B}'..='\u{10FFFF}'
+    )
 }
 
 #[inline]
 pub fn is_any_char(c: char) -> bool {
-    match c {
-        '\u{0000}'..='\u{0009}' | '\u{000B}'..='\u{000C}' | '\u{000E}'..='\u{10FFFF}' => true,
-        _ => false,
-    }
+    matches!(
+        c,
+        '\u{0000}'..='\u{0009}' | '\u{000B}'..='\u{000C}' | '\u{000E}'..='\u{10FFFF}'
+    )
 }
 
 #[inline]
@@ -89,7 +84,7 @@ fn end_of_line(input: &str) -> IResult<&str, &str> {
 }
 
 #[inline]
-fn comment(input: &str) -> IResult<&str, RawEventLine> {
+fn comment(input: &str) -> IResult<&str, RawEventLine<'_>> {
     preceded(
         take_while_m_n(1, 1, is_colon),
         terminated(take_while(is_any_char), end_of_line),
@@ -98,7 +93,7 @@ fn comment(input: &str) -> IResult<&str, RawEventLine> {
 }
 
 #[inline]
-fn field(input: &str) -> IResult<&str, RawEventLine> {
+fn field(input: &str) -> IResult<&str, RawEventLine<'_>> {
     terminated(
         tuple((
             take_while1(is_name_char),
@@ -113,10 +108,10 @@ fn field(input: &str) -> IResult<&str, RawEventLine> {
 }
 
 #[inline]
-fn empty(input: &str) -> IResult<&str, RawEventLine> {
+fn empty(input: &str) -> IResult<&str, RawEventLine<'_>> {
     end_of_line(input).map(|(i, _)| (i, RawEventLine::Empty))
 }
 
-pub fn line(input: &str) -> IResult<&str, RawEventLine> {
+pub fn line(input: &str) -> IResult<&str, RawEventLine<'_>> {
     alt((comment, field, empty))(input)
 }
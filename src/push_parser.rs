@@ -0,0 +1,729 @@
+#[cfg(not(feature = "std"))]
+use alloc::string::{FromUtf8Error, String, ToString};
+
+use crate::builder::{EndOfLines, EventBuffer, Input, Line, Parsed, ParserCore, SetField};
+use crate::event::{Event, EventRef, EventStreamItem};
+use crate::event_stream::EventStreamError;
+use crate::limits::Limits;
+use crate::parser::{is_lf, line, RawEventLine};
+use crate::utf8::{Utf8Decoder, Utf8Policy};
+use core::convert::Infallible;
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::string::FromUtf8Error;
+
+/// The event being built by a [`Parser`], whose buffers are reused for every event
+#[derive(Default, Debug)]
+struct EventFields {
+    event: Event,
+    last_event_id: String,
+}
+
+impl EventBuffer for EventFields {
+    type Event = Event;
+
+    fn has_data(&self) -> bool {
+        !self.event.data.is_empty()
+    }
+
+    fn data_len(&self) -> usize {
+        self.event.data.len()
+    }
+
+    fn set_retry(&mut self, retry: Duration) {
+        self.event.retry = Some(retry);
+    }
+
+    fn commit_id(&mut self) -> bool {
+        if self.last_event_id == self.event.id {
+            return false;
+        }
+        self.last_event_id.clone_from(&self.event.id);
+        true
+    }
+
+    fn clear(&mut self) {
+        self.event.event.clear();
+        self.event.data.clear();
+        self.event.retry = None;
+        self.event.id.clone_from(&self.last_event_id);
+    }
+
+    /// The event is swapped into `out`, so the buffers of the dispatched event are reused for the
+    /// next one
+    fn take_into(&mut self, out: &mut Event) {
+        core::mem::swap(&mut self.event, out);
+        if out.data.ends_with(is_lf) {
+            out.data.pop();
+        }
+    }
+}
+
+impl SetField<&str> for EventFields {
+    fn set_event(&mut self, value: &str) {
+        self.event.event.clear();
+        self.event.event.push_str(value);
+    }
+
+    fn push_data(&mut self, value: &str) {
+        self.event.data.push_str(value);
+        self.event.data.push('\u{000A}');
+    }
+
+    fn set_id(&mut self, value: &str) {
+        self.event.id.clear();
+        self.event.id.push_str(value);
+    }
+}
+
+/// Text received from the source which has not been parsed yet. Parsed lines are skipped over
+/// with a read cursor and only dropped from the front of the buffer by [`LineBuffer::compact`],
+/// so parsing a chunk of many lines takes linear time. Bytes held by the decoder belong to the
+/// incomplete line at the end.
+#[derive(Default, Debug)]
+struct LineBuffer {
+    decoder: Utf8Decoder,
+    buffer: String,
+    pos: usize,
+    /// Start of the line whose terminator was not received yet
+    line_start: usize,
+    is_discarding: bool,
+}
+
+impl LineBuffer {
+    fn remaining(&self) -> &str {
+        &self.buffer[self.pos..]
+    }
+
+    fn consume(&mut self, len: usize) {
+        self.pos += len;
+    }
+
+    /// Whether a line terminator was received after the read cursor
+    fn has_terminator(&self) -> bool {
+        self.line_start > self.pos
+    }
+
+    fn compact(&mut self) {
+        if self.is_empty() {
+            self.buffer.clear();
+        } else {
+            self.buffer.drain(..self.pos);
+        }
+        self.line_start -= self.pos;
+        self.pos = 0;
+    }
+
+    /// Decode and append the next chunk. On invalid bytes under [`Utf8Policy::Strict`] the
+    /// text before them is still appended.
+    fn push_bytes(&mut self, bytes: &[u8]) -> Result<(), FromUtf8Error> {
+        // A discarded line is dropped before decoding, so its bytes are never held
+        let bytes = if self.is_discarding {
+            match bytes.iter().position(|b| *b == b'\n' || *b == b'\r') {
+                Some(pos) => {
+                    self.is_discarding = false;
+                    &bytes[pos..]
+                }
+                None => return Ok(()),
+            }
+        } else {
+            bytes
+        };
+        if bytes.is_empty() {
+            return Ok(());
+        }
+        self.compact();
+        match self.decoder.decode(bytes) {
+            Ok(string) => {
+                self.push_str(&string);
+                Ok(())
+            }
+            Err(err) => {
+                let valid = &err.as_bytes()[..err.utf8_error().valid_up_to()];
+                self.push_str(core::str::from_utf8(valid).unwrap_or_default());
+                Err(err)
+            }
+        }
+    }
+
+    fn push_str(&mut self, string: &str) {
+        if let Some(idx) = string.rfind(['\u{000A}', '\u{000D}']) {
+            self.line_start = self.buffer.len() + idx + 1;
+        }
+        self.buffer.push_str(string);
+    }
+}
+
+impl Input for LineBuffer {
+    fn is_empty(&self) -> bool {
+        self.pos == self.buffer.len()
+    }
+
+    fn partial_len(&self) -> usize {
+        self.buffer.len() - self.line_start + self.decoder.held_len()
+    }
+
+    fn buffered_len(&self) -> usize {
+        self.buffer.len() - self.pos + self.decoder.held_len()
+    }
+
+    fn is_discarding(&self) -> bool {
+        self.is_discarding
+    }
+
+    fn clear(&mut self) {
+        self.decoder.clear();
+        self.buffer.clear();
+        self.pos = 0;
+        self.line_start = 0;
+        self.is_discarding = false;
+    }
+
+    fn discard_line(&mut self) {
+        self.decoder.clear();
+        self.buffer.truncate(self.line_start);
+        self.buffer.push(':');
+        self.is_discarding = true;
+    }
+
+    fn end_line(&mut self) {
+        self.buffer.push('\u{000A}');
+        self.line_start = self.buffer.len();
+    }
+}
+
+/// How the source stream ended
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum StreamEnd {
+    /// The source ended on an event boundary
+    Clean,
+    /// The source ended with a partial line or an undispatched event
+    MidEvent,
+    /// The parser stopped before the source ended, on an exceeded limit under
+    /// [`crate::LimitAction::Fail`] or on invalid bytes under [`Utf8Policy::Strict`]
+    Aborted,
+}
+
+/// A runtime agnostic push parser of `text/event-stream` bytes, which [`crate::EventStream`] and
+/// [`crate::EventIter`] are built on
+///
+/// Bytes are passed in with [`Parser::feed`] as they arrive from any source, and parsed events
+/// are pulled out with [`Parser::next_event`] until it returns `None`. The parser carries
+/// incomplete UTF-8 sequences and lines over to the next chunk, so chunks may be split anywhere.
+/// Call [`Parser::finish`] once the source has ended.
+///
+/// ```
+/// use eventsource_stream::Parser;
+///
+/// let mut parser = Parser::new();
+/// parser.feed(b"data: Hello,").unwrap();
+/// assert!(parser.next_event().is_none());
+/// parser.feed(b" world!\n\n").unwrap();
+/// assert_eq!(parser.next_event().unwrap().unwrap().data, "Hello, world!");
+/// ```
+#[derive(Debug, Default)]
+pub struct Parser {
+    buffer: LineBuffer,
+    core: ParserCore<EventFields>,
+    is_extended: bool,
+    /// Invalid bytes under [`Utf8Policy::Strict`], returned once the lines before them were
+    /// parsed
+    utf8_error: Option<FromUtf8Error>,
+}
+
+impl Parser {
+    /// Create a parser with the default settings
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start from a known last event ID, see [`crate::EventStream::with_last_event_id`]
+    pub fn with_last_event_id(mut self, last_event_id: impl Into<String>) -> Self {
+        let fields = &mut self.core.builder.buffer;
+        fields.last_event_id = last_event_id.into();
+        fields.event.id.clone_from(&fields.last_event_id);
+        self
+    }
+
+    /// Start from a known reconnection time, see [`crate::EventStream::with_retry`]
+    pub fn with_retry(mut self, retry: Duration) -> Self {
+        self.core.builder.retry = Some(retry);
+        self
+    }
+
+    /// Dispatch a partly built event when the parser is finished, see
+    /// [`crate::EventStream::with_flush_on_eof`]
+    pub fn with_flush_on_eof(mut self, flush_on_eof: bool) -> Self {
+        self.core.flush_on_eof = flush_on_eof;
+        self
+    }
+
+    /// Set how invalid UTF-8 in the input is handled. Defaults to [`Utf8Policy::Lossy`].
+    pub fn with_utf8_policy(mut self, policy: Utf8Policy) -> Self {
+        self.buffer.decoder.set_policy(policy);
+        self
+    }
+
+    /// Set the size limits applied to lines, events and buffered data
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.core.limits = limits;
+        self
+    }
+
+    /// Also return comments and fields which are not part of the spec from
+    /// [`Parser::next_item`]. Disabled by default.
+    pub fn with_extended(mut self, is_extended: bool) -> Self {
+        self.is_extended = is_extended;
+        self
+    }
+
+    /// Get the last event ID, as set by the last blank line whether or not it dispatched an
+    /// event
+    pub fn last_event_id(&self) -> &str {
+        &self.core.builder.buffer.last_event_id
+    }
+
+    /// Get the reconnection time last set by a `retry` field, whether or not an event was
+    /// dispatched with it
+    pub fn retry(&self) -> Option<Duration> {
+        self.core.builder.retry
+    }
+
+    /// Get how the input ended, or `None` until the parser is finished and all buffered input
+    /// was parsed. [`StreamEnd::Aborted`] is returned as soon as the parser stopped on an error.
+    pub fn stream_end(&self) -> Option<StreamEnd> {
+        self.core.stream_end()
+    }
+
+    /// Whether the parser takes no more input, because it was finished or failed on a limit
+    pub(crate) fn is_terminated(&self) -> bool {
+        self.core.is_terminated()
+    }
+
+    /// Pass the next chunk of bytes to the parser. Input fed after the parser was finished is
+    /// ignored.
+    ///
+    /// The chunk is refused with [`Limit::Buffered`](crate::Limit::Buffered) if more than
+    /// [`Limits::max_buffered`] bytes fed earlier were not parsed yet. The parser is left
+    /// unchanged, so the chunk can be fed again once the parsed items were pulled.
+    ///
+    /// Errors in the input are reported by [`Parser::next_item`] after the items before them.
+    /// Invalid bytes under [`Utf8Policy::Strict`] end the input: the lines before them are still
+    /// parsed, then the error is returned and the parser is terminated. An incomplete line
+    /// exceeding [`Limits::max_buffered`] is reported the same way.
+    pub fn feed(&mut self, bytes: &[u8]) -> Result<(), EventStreamError<Infallible>> {
+        if self.core.is_terminated() {
+            return Ok(());
+        }
+        self.core.check_buffered(&self.buffer)?;
+        self.push(bytes);
+        Ok(())
+    }
+
+    /// Pass the next chunk without checking [`Limits::max_buffered`], for the adapters which
+    /// pull every item before the next chunk. Only the incomplete line is held then, which is
+    /// checked by [`Parser::next_item`], so a chunk is never refused and lost.
+    pub(crate) fn push(&mut self, bytes: &[u8]) {
+        if self.core.is_terminated() {
+            return;
+        }
+        let (held, bom_len) = self.core.bom.strip(bytes);
+        if self.push_bytes(held) {
+            self.push_bytes(&bytes[bom_len..]);
+        }
+    }
+
+    /// Mark the end of the input. A partly built event is dispatched by the following calls to
+    /// [`Parser::next_event`] if enabled by [`Parser::with_flush_on_eof`], otherwise it is
+    /// dropped.
+    ///
+    /// An incomplete UTF-8 sequence at the end of the input under [`Utf8Policy::Strict`] is
+    /// reported by [`Parser::next_item`] like invalid bytes passed to [`Parser::feed`].
+    pub fn finish(&mut self) {
+        if self.core.is_terminated() {
+            return;
+        }
+        let held = self.core.bom.finish();
+        if self.push_bytes(held) {
+            match self.buffer.decoder.finish() {
+                Some(Ok(string)) => self.buffer.push_str(&string),
+                Some(Err(err)) => self.fail_input(err),
+                None => {}
+            }
+        }
+        if self.core.is_terminated() {
+            return;
+        }
+        // A CR at the end can no longer be the start of a CRLF
+        if self.buffer.remaining().ends_with('\u{000D}') {
+            self.buffer.end_line();
+        }
+        self.core.finish();
+    }
+
+    /// Parse the next event from the input fed so far, or `None` if more input is needed
+    pub fn next_event(&mut self) -> Option<Result<Event, EventStreamError<Infallible>>> {
+        loop {
+            match self.next_item()? {
+                Ok(EventStreamItem::Event(event)) => return Some(Ok(event)),
+                Ok(_) => {}
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+
+    /// Parse the next event like [`Parser::next_event`], but borrow it from the parser instead
+    /// of allocating it. The buffers of the event are reused once the parser is called again,
+    /// so consumers which only filter or forward events do not allocate per event.
+    ///
+    /// ```
+    /// use eventsource_stream::Parser;
+    ///
+    /// let mut parser = Parser::new();
+    /// parser.feed(b"event: tick\ndata: 1\n\nevent: add\ndata: 2\n\n").unwrap();
+    /// while let Some(event) = parser.next_event_ref() {
+    ///     let event = event.unwrap();
+    ///     if event.event == "add" {
+    ///         assert_eq!(event.data, "2");
+    ///     }
+    /// }
+    /// ```
+    pub fn next_event_ref(&mut self) -> Option<Result<EventRef<'_>, EventStreamError<Infallible>>> {
+        loop {
+            match self.parse_next()? {
+                Ok(Parsed::Event) => return Some(Ok(EventRef::from(&self.core.dispatched))),
+                Ok(_) => {}
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+
+    /// Parse the next item from the input fed so far, or `None` if more input is needed. Only
+    /// events are returned unless [`Parser::with_extended`] is enabled.
+    pub fn next_item(&mut self) -> Option<Result<EventStreamItem, EventStreamError<Infallible>>> {
+        let parsed = self.parse_next()?;
+        Some(parsed.map(|parsed| match parsed {
+            Parsed::Comment(comment) => EventStreamItem::Comment(comment),
+            Parsed::Field(field, value) => EventStreamItem::Field(field, value),
+            Parsed::Retry(retry) => EventStreamItem::Retry(retry),
+            Parsed::Id => EventStreamItem::Id(self.last_event_id().to_string()),
+            Parsed::Event => EventStreamItem::Event(core::mem::take(&mut self.core.dispatched)),
+        }))
+    }
+
+    fn parse_next(&mut self) -> Option<Result<Parsed<String>, EventStreamError<Infallible>>> {
+        loop {
+            let input = self.buffer.remaining();
+            // Without a terminator the line is not scanned again for every chunk it spans, which
+            // would take quadratic time
+            let parsed = if self.buffer.has_terminator() {
+                line(input)
+            } else {
+                Err(nom::Err::Incomplete(nom::Needed::Unknown))
+            };
+            match parsed {
+                Ok((rem, next_line)) => {
+                    let consumed = input.len() - rem.len();
+                    let len = line_length(&input[..consumed]);
+                    let next_line = match next_line {
+                        RawEventLine::Comment(comment) => Line::Comment(comment),
+                        RawEventLine::Field(field, value) => {
+                            Line::Field(field, value.unwrap_or(""))
+                        }
+                        RawEventLine::Empty => Line::Empty,
+                    };
+                    let parsed = match self.core.parse_line(next_line, len) {
+                        Ok(Some(Parsed::Event)) => Some(Parsed::Event),
+                        Ok(Some(parsed)) if self.is_extended => Some(parsed.map(str::to_string)),
+                        Ok(_) => None,
+                        Err(limit) => {
+                            self.buffer.consume(consumed);
+                            return Some(Err(self.core.limit_exceeded(
+                                limit,
+                                &mut self.buffer,
+                                false,
+                            )));
+                        }
+                    };
+                    self.buffer.consume(consumed);
+                    if let Some(parsed) = parsed {
+                        return Some(Ok(parsed));
+                    }
+                }
+                Err(nom::Err::Incomplete(_)) => {
+                    if let Some(err) = self.utf8_error.take() {
+                        return Some(Err(EventStreamError::Utf8(err)));
+                    }
+                    if let Err(err) = self.core.check_partial(&mut self.buffer) {
+                        return Some(Err(err));
+                    }
+                    match self.core.end_of_lines(&mut self.buffer) {
+                        EndOfLines::Pending => return None,
+                        EndOfLines::Flush => {}
+                        EndOfLines::Event => return Some(Ok(Parsed::Event)),
+                    }
+                }
+                Err(nom::Err::Error(err)) | Err(nom::Err::Failure(err)) => {
+                    return Some(Err(err.into()))
+                }
+            }
+        }
+    }
+
+    /// Append the next bytes, returning `false` if they ended the input
+    fn push_bytes(&mut self, bytes: &[u8]) -> bool {
+        match self.buffer.push_bytes(bytes) {
+            Ok(()) => true,
+            Err(err) => {
+                self.fail_input(err);
+                false
+            }
+        }
+    }
+
+    fn fail_input(&mut self, err: FromUtf8Error) {
+        self.core.fail_input(&mut self.buffer);
+        self.utf8_error = Some(err);
+    }
+}
+
+#[inline]
+fn line_length(line: &str) -> usize {
+    line.trim_end_matches(['\u{000A}', '\u{000D}']).len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::limits::{Limit, LimitAction};
+
+    #[test]
+    fn feed_bytes() {
+        let mut parser = Parser::new();
+        let mut events = Vec::new();
+        for byte in "\u{feff}id: 1\r\ndata: 👍\r\n\r\ndata: 2\n\nid: 3\n\n".as_bytes() {
+            parser.feed(core::slice::from_ref(byte)).unwrap();
+            while let Some(event) = parser.next_event() {
+                events.push(event.unwrap());
+            }
+        }
+        parser.finish();
+        assert!(parser.next_event().is_none());
+        assert_eq!(
+            events,
+            vec![
+                Event {
+                    id: "1".to_string(),
+                    data: "👍".to_string(),
+                    ..Default::default()
+                },
+                Event {
+                    id: "1".to_string(),
+                    data: "2".to_string(),
+                    ..Default::default()
+                }
+            ]
+        );
+        assert_eq!(parser.last_event_id(), "3");
+        assert_eq!(parser.stream_end(), Some(StreamEnd::Clean));
+    }
+
+    #[test]
+    fn finish() {
+        let mut parser = Parser::new().with_flush_on_eof(true).with_extended(true);
+        parser.feed(b"data: a\n\n: ping\ndata: b").unwrap();
+        assert!(matches!(
+            parser.next_item(),
+            Some(Ok(EventStreamItem::Event(_)))
+        ));
+        assert_eq!(
+            parser.next_item(),
+            Some(Ok(EventStreamItem::Comment(" ping".to_string())))
+        );
+        assert!(parser.next_item().is_none());
+        parser.finish();
+        assert_eq!(parser.next_event().unwrap().unwrap().data, "b");
+        assert!(parser.next_item().is_none());
+        assert_eq!(parser.stream_end(), Some(StreamEnd::MidEvent));
+
+        parser.feed(b"data: c\n\n").unwrap();
+        assert!(parser.next_item().is_none());
+
+        let mut parser = Parser::new();
+        parser.feed(b"data: a\n\n").unwrap();
+        parser.finish();
+        assert_eq!(parser.stream_end(), None);
+        assert_eq!(parser.next_event().unwrap().unwrap().data, "a");
+        assert!(parser.next_event().is_none());
+        assert_eq!(parser.stream_end(), Some(StreamEnd::Clean));
+
+        let mut parser = Parser::new().with_utf8_policy(Utf8Policy::Strict);
+        parser.feed(b"data: a\n\nd\xf0\x9f").unwrap();
+        parser.finish();
+        assert_eq!(parser.next_event().unwrap().unwrap().data, "a");
+        assert!(matches!(
+            parser.next_event(),
+            Some(Err(EventStreamError::Utf8(_)))
+        ));
+        assert!(parser.next_event().is_none());
+        assert_eq!(parser.stream_end(), Some(StreamEnd::Aborted));
+    }
+
+    #[test]
+    fn invalid_utf8() {
+        let mut parser = Parser::new().with_utf8_policy(Utf8Policy::Strict);
+        parser.feed(b"data: z\n\ndata: a\xff\n\n").unwrap();
+        parser.feed(b"data: b\n\n").unwrap();
+        assert_eq!(parser.next_event().unwrap().unwrap().data, "z");
+        assert!(matches!(
+            parser.next_event(),
+            Some(Err(EventStreamError::Utf8(_)))
+        ));
+        assert!(parser.next_event().is_none());
+        assert_eq!(parser.stream_end(), Some(StreamEnd::Aborted));
+
+        // The limits apply to the incomplete line held back by the decoder
+        let mut parser = Parser::new()
+            .with_utf8_policy(Utf8Policy::SkipLine)
+            .with_limits(Limits {
+                max_line_length: Some(16),
+                max_buffered: Some(1024),
+                on_exceeded: LimitAction::Skip,
+                ..Default::default()
+            });
+        parser.feed(b"data: 0123456789").unwrap();
+        parser.feed(b"abcdef").unwrap();
+        assert_eq!(
+            parser.next_event(),
+            Some(Err(EventStreamError::LimitExceeded(Limit::LineLength)))
+        );
+        parser.feed(&[b'a'; 2048]).unwrap();
+        parser.feed(b"\n\ndata: b\n\n").unwrap();
+        assert_eq!(parser.next_event().unwrap().unwrap().data, "b");
+        assert!(parser.next_event().is_none());
+
+        let mut parser = Parser::new()
+            .with_utf8_policy(Utf8Policy::SkipLine)
+            .with_limits(Limits {
+                max_buffered: Some(1024),
+                ..Default::default()
+            });
+        parser.feed(&[b'a'; 1024]).unwrap();
+        assert!(parser.next_event().is_none());
+        parser.feed(b"a").unwrap();
+        assert_eq!(
+            parser.next_event(),
+            Some(Err(EventStreamError::LimitExceeded(Limit::Buffered)))
+        );
+    }
+
+    #[test]
+    fn max_buffered() {
+        let mut parser = Parser::new().with_limits(Limits {
+            max_buffered: Some(1024),
+            ..Default::default()
+        });
+        parser.feed("data: x\n\n".repeat(1000).as_bytes()).unwrap();
+        assert_eq!(core::iter::from_fn(|| parser.next_event()).count(), 1000);
+        parser.feed(&[b'a'; 1024]).unwrap();
+        assert!(parser.next_event().is_none());
+        parser.feed(b"a").unwrap();
+        assert_eq!(
+            parser.next_event(),
+            Some(Err(EventStreamError::LimitExceeded(Limit::Buffered)))
+        );
+        assert_eq!(parser.stream_end(), Some(StreamEnd::Aborted));
+
+        // Input fed without pulling the parsed items is refused until they were pulled
+        let mut parser = Parser::new().with_limits(Limits {
+            max_buffered: Some(24),
+            ..Default::default()
+        });
+        parser.feed(b"data: a\n\ndata: b\n\n").unwrap();
+        parser.feed(b"data: c\n\n").unwrap();
+        assert_eq!(
+            parser.feed(b"data: d\n\n"),
+            Err(EventStreamError::LimitExceeded(Limit::Buffered))
+        );
+        assert_eq!(parser.next_event().unwrap().unwrap().data, "a");
+        assert_eq!(parser.next_event().unwrap().unwrap().data, "b");
+        parser.feed(b"data: d\n\n").unwrap();
+        assert_eq!(parser.next_event().unwrap().unwrap().data, "c");
+        assert_eq!(parser.next_event().unwrap().unwrap().data, "d");
+        assert!(parser.next_event().is_none());
+
+        // The line being discarded is not counted
+        let mut parser = Parser::new().with_limits(Limits {
+            max_buffered: Some(0),
+            on_exceeded: LimitAction::Skip,
+            ..Default::default()
+        });
+        parser.feed(b"data: a").unwrap();
+        assert_eq!(
+            parser.next_event(),
+            Some(Err(EventStreamError::LimitExceeded(Limit::Buffered)))
+        );
+        parser.feed(b"bc").unwrap();
+        assert!(parser.next_event().is_none());
+        parser.feed(b"\n\n").unwrap();
+        assert!(parser.next_event().is_none());
+        parser.feed(b"data: ok\n\n").unwrap();
+        assert_eq!(parser.next_event().unwrap().unwrap().data, "ok");
+
+        // The events completed in the same chunk as the oversized line are kept
+        for on_exceeded in [LimitAction::Skip, LimitAction::Fail] {
+            let mut parser = Parser::new().with_limits(Limits {
+                max_buffered: Some(16),
+                on_exceeded,
+                ..Default::default()
+            });
+            parser
+                .feed(b"data: ok\n\ndata: 0123456789abcdefghij")
+                .unwrap();
+            assert_eq!(parser.next_event().unwrap().unwrap().data, "ok");
+            assert_eq!(
+                parser.next_event(),
+                Some(Err(EventStreamError::LimitExceeded(Limit::Buffered)))
+            );
+            parser.feed(b"klm\n\ndata: b\n\n").unwrap();
+            let next = parser.next_event().map(|event| event.unwrap().data);
+            match on_exceeded {
+                LimitAction::Skip => assert_eq!(next.as_deref(), Some("b")),
+                LimitAction::Fail => assert_eq!(next, None),
+            }
+        }
+    }
+
+    #[test]
+    fn borrowed_events() {
+        let mut parser = Parser::new().with_extended(true);
+        parser
+            .feed(b"id: 1\ndata: aaaa\n\n: ping\ndata: bbbb\ndata: c\n\ndata: dddd\n\n")
+            .unwrap();
+
+        let first = parser.next_event_ref().unwrap().unwrap();
+        assert_eq!(
+            first,
+            EventRef {
+                event: "",
+                data: "aaaa",
+                id: "1",
+                retry: None
+            }
+        );
+        let first_data = first.data.as_ptr();
+        assert_eq!(parser.next_event_ref().unwrap().unwrap().data, "bbbb\nc");
+        let third = parser.next_event_ref().unwrap().unwrap();
+        assert_eq!(
+            Event::from(third),
+            Event {
+                data: "dddd".to_string(),
+                id: "1".to_string(),
+                ..Default::default()
+            }
+        );
+        assert_eq!(third.data.as_ptr(), first_data);
+        assert!(parser.next_event_ref().is_none());
+        assert_eq!(parser.last_event_id(), "1");
+    }
+}
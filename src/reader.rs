@@ -0,0 +1,151 @@
+use core::pin::Pin;
+use futures_core::ready;
+use futures_core::stream::Stream;
+use futures_core::task::{Context, Poll};
+use pin_project::pin_project;
+use std::io;
+
+/// A Stream of the chunks of bytes read from a `futures_io::AsyncRead`, see
+/// [`crate::AsyncReadEventsource`]
+#[cfg(feature = "futures-io")]
+#[pin_project]
+pub struct AsyncReadChunks<R> {
+    #[pin]
+    reader: R,
+    buffer: Box<[u8]>,
+    is_done: bool,
+}
+
+#[cfg(feature = "futures-io")]
+impl<R> AsyncReadChunks<R> {
+    pub(crate) fn new(reader: R, capacity: usize) -> Self {
+        Self {
+            reader,
+            buffer: vec![0; capacity.max(1)].into_boxed_slice(),
+            is_done: false,
+        }
+    }
+
+    /// Get a reference to the underlying reader
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+}
+
+#[cfg(feature = "futures-io")]
+impl<R: futures_io::AsyncRead> Stream for AsyncReadChunks<R> {
+    type Item = io::Result<Vec<u8>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        while !*this.is_done {
+            match ready!(this.reader.as_mut().poll_read(cx, this.buffer)) {
+                Ok(0) => *this.is_done = true,
+                Ok(len) => return Poll::Ready(Some(Ok(this.buffer[..len].to_vec()))),
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Poll::Ready(Some(Err(err))),
+            }
+        }
+        Poll::Ready(None)
+    }
+}
+
+/// A Stream of the chunks of bytes read from a `tokio::io::AsyncRead`, see
+/// [`crate::TokioEventsource`]
+#[cfg(feature = "tokio")]
+#[pin_project]
+pub struct TokioReadChunks<R> {
+    #[pin]
+    reader: R,
+    buffer: Box<[u8]>,
+    is_done: bool,
+}
+
+#[cfg(feature = "tokio")]
+impl<R> TokioReadChunks<R> {
+    pub(crate) fn new(reader: R, capacity: usize) -> Self {
+        Self {
+            reader,
+            buffer: vec![0; capacity.max(1)].into_boxed_slice(),
+            is_done: false,
+        }
+    }
+
+    /// Get a reference to the underlying reader
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<R: tokio::io::AsyncRead> Stream for TokioReadChunks<R> {
+    type Item = io::Result<Vec<u8>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        while !*this.is_done {
+            let mut buf = tokio::io::ReadBuf::new(this.buffer);
+            match ready!(this.reader.as_mut().poll_read(cx, &mut buf)) {
+                Ok(()) if buf.filled().is_empty() => *this.is_done = true,
+                Ok(()) => return Poll::Ready(Some(Ok(buf.filled().to_vec()))),
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Poll::Ready(Some(Err(err))),
+            }
+        }
+        Poll::Ready(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Event;
+    use futures::prelude::*;
+
+    fn expected() -> Vec<Event> {
+        vec![
+            Event {
+                event: "add".to_string(),
+                data: "👍".to_string(),
+                ..Default::default()
+            },
+            Event {
+                data: "2".to_string(),
+                ..Default::default()
+            },
+        ]
+    }
+
+    const INPUT: &[u8] = "event: add\ndata: 👍\n\ndata: 2\n\n".as_bytes();
+
+    #[cfg(feature = "futures-io")]
+    #[tokio::test]
+    async fn futures_reader() {
+        use crate::AsyncReadEventsource;
+
+        let stream = INPUT.read_eventsource_with_capacity(3);
+        assert_eq!(stream.try_collect::<Vec<_>>().await.unwrap(), expected());
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn tokio_reader() {
+        use crate::TokioEventsource;
+
+        let stream = INPUT.tokio_eventsource_with_capacity(3);
+        assert_eq!(stream.try_collect::<Vec<_>>().await.unwrap(), expected());
+
+        let stream = tokio::io::empty().tokio_eventsource();
+        assert_eq!(stream.try_collect::<Vec<_>>().await.unwrap(), vec![]);
+    }
+
+    #[cfg(all(feature = "futures-io", feature = "tokio"))]
+    #[tokio::test]
+    async fn both_readers() {
+        use crate::{AsyncReadEventsource, TokioEventsource};
+
+        let stream = INPUT.read_eventsource();
+        assert_eq!(stream.try_collect::<Vec<_>>().await.unwrap(), expected());
+        let stream = INPUT.tokio_eventsource();
+        assert_eq!(stream.try_collect::<Vec<_>>().await.unwrap(), expected());
+    }
+}
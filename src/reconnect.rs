@@ -0,0 +1,429 @@
+use crate::event::Event;
+use crate::event_stream::{EventStream, EventStreamError};
+use crate::limits::Limits;
+use crate::traits::Eventsource;
+use crate::utf8::Utf8Policy;
+use core::fmt;
+use core::future::Future;
+use core::pin::Pin;
+use core::time::Duration;
+use futures_core::ready;
+use futures_core::stream::Stream;
+use futures_core::task::{Context, Poll};
+use pin_project::pin_project;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+/// How a [`ReconnectingEventStream`] waits between connection attempts and parses every
+/// connection
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReconnectOptions {
+    /// Reconnection time used until the server sends a `retry` field
+    pub retry: Duration,
+    /// Factor the reconnection time is multiplied with for every consecutive failed attempt
+    pub backoff: f64,
+    /// Upper bound for the backed off reconnection time. A larger `retry` sent by the server is
+    /// still honored.
+    pub max_delay: Duration,
+    /// Fraction of the delay, between 0 and 1, which is randomly subtracted from it so that
+    /// clients do not reconnect all at once
+    pub jitter: f64,
+    /// Maximum number of consecutive failed attempts before the stream ends, unlimited if `None`.
+    /// An attempt succeeds once `connect` resolves, whether or not the connection carries events.
+    pub max_retries: Option<usize>,
+    /// Size limits of every connection, see [`EventStream::with_limits`]. With
+    /// [`crate::LimitAction::Fail`] an exceeded limit ends the connection and a new one is opened.
+    pub limits: Limits,
+    /// How invalid UTF-8 is handled, see [`EventStream::with_utf8_policy`]
+    pub utf8_policy: Utf8Policy,
+    /// Dispatch a partly built event when a connection ends, see
+    /// [`EventStream::with_flush_on_eof`]
+    pub flush_on_eof: bool,
+}
+
+impl Default for ReconnectOptions {
+    fn default() -> Self {
+        Self {
+            retry: Duration::from_secs(3),
+            backoff: 2.0,
+            max_delay: Duration::from_secs(60),
+            jitter: 0.2,
+            max_retries: None,
+            limits: Limits::default(),
+            utf8_policy: Utf8Policy::default(),
+            flush_on_eof: false,
+        }
+    }
+}
+
+/// Error yielded by a [`ReconnectingEventStream`]. The stream reconnects after a connection
+/// error or a transport error and keeps going after parse errors.
+#[derive(Debug, PartialEq)]
+pub enum ReconnectError<C, E> {
+    /// The connect function failed
+    Connect(C),
+    /// The event stream of the current connection failed
+    Stream(EventStreamError<E>),
+}
+
+impl<C, E> fmt::Display for ReconnectError<C, E>
+where
+    C: fmt::Display,
+    E: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Connect(err) => f.write_fmt(format_args!("Connect error: {}", err)),
+            Self::Stream(err) => err.fmt(f),
+        }
+    }
+}
+
+impl<C, E> std::error::Error for ReconnectError<C, E>
+where
+    C: fmt::Display + fmt::Debug,
+    E: fmt::Display + fmt::Debug + Send + Sync,
+{
+}
+
+#[allow(clippy::large_enum_variant)]
+#[pin_project(project = StateProj)]
+enum State<F, S, D> {
+    Connecting(#[pin] F),
+    Streaming(#[pin] EventStream<S>),
+    Waiting(#[pin] D),
+    Done,
+}
+
+/// A Stream of events which reconnects whenever the underlying stream ends or fails
+///
+/// `connect` is called with the last event ID, empty if none was received yet, and should open a
+/// new stream of bytes, sending the ID as `Last-Event-ID` header if not empty. `sleep` should
+/// return a future which completes after the given duration, for example `tokio::time::sleep`.
+#[pin_project]
+pub struct ReconnectingEventStream<C, F, S, Sl, D> {
+    connect: C,
+    sleep: Sl,
+    options: ReconnectOptions,
+    #[pin]
+    state: State<F, S, D>,
+    last_event_id: String,
+    retry: Duration,
+    attempts: usize,
+    rng: u64,
+}
+
+impl<C, F, S, Sl, D> ReconnectingEventStream<C, F, S, Sl, D>
+where
+    C: FnMut(&str) -> F,
+    Sl: FnMut(Duration) -> D,
+{
+    /// Create a stream which connects immediately and reconnects with the default
+    /// [`ReconnectOptions`]
+    pub fn new(connect: C, sleep: Sl) -> Self {
+        Self::with_options(connect, sleep, ReconnectOptions::default())
+    }
+
+    /// Create a stream which connects immediately and reconnects with `options`
+    pub fn with_options(mut connect: C, sleep: Sl, options: ReconnectOptions) -> Self {
+        let state = State::Connecting(connect(""));
+        Self {
+            connect,
+            sleep,
+            retry: options.retry,
+            options,
+            state,
+            last_event_id: String::new(),
+            attempts: 0,
+            rng: RandomState::new().build_hasher().finish() | 1,
+        }
+    }
+}
+
+impl<C, F, S, Sl, D> ReconnectingEventStream<C, F, S, Sl, D> {
+    /// Get the last event ID received over any of the connections
+    pub fn last_event_id(&self) -> &str {
+        &self.last_event_id
+    }
+
+    /// Get the current reconnection time, as last set by the server
+    pub fn retry(&self) -> Duration {
+        self.retry
+    }
+}
+
+impl ReconnectOptions {
+    fn delay(&self, retry: Duration, attempts: usize, rng: &mut u64) -> Duration {
+        // a non-finite factor would turn every delay into NaN or infinity
+        let backoff = if self.backoff.is_finite() {
+            self.backoff.max(1.0)
+        } else {
+            1.0
+        };
+        let jitter = if self.jitter.is_finite() {
+            self.jitter.clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let max_delay = self.max_delay.max(retry);
+        let backoff = backoff.powi(attempts.min(i32::MAX as usize) as i32);
+        let delay = Duration::try_from_secs_f64(retry.as_secs_f64() * backoff)
+            .map_or(max_delay, |delay| delay.min(max_delay));
+
+        // xorshift64
+        *rng ^= *rng << 13;
+        *rng ^= *rng >> 7;
+        *rng ^= *rng << 17;
+        let random = (*rng >> 11) as f64 / (1u64 << 53) as f64;
+        Duration::try_from_secs_f64(delay.as_secs_f64() * (1.0 - jitter * random))
+            .map_or(delay, |jittered| jittered.min(delay))
+    }
+}
+
+impl<C, F, S, Sl, D, CE, B, E> Stream for ReconnectingEventStream<C, F, S, Sl, D>
+where
+    C: FnMut(&str) -> F,
+    F: Future<Output = Result<S, CE>>,
+    S: Stream<Item = Result<B, E>>,
+    B: AsRef<[u8]>,
+    Sl: FnMut(Duration) -> D,
+    D: Future<Output = ()>,
+{
+    type Item = Result<Event, ReconnectError<CE, E>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        loop {
+            let err = match this.state.as_mut().project() {
+                StateProj::Connecting(connecting) => match ready!(connecting.poll(cx)) {
+                    Ok(stream) => {
+                        // A quiet server which only sends comments is not failing
+                        *this.attempts = 0;
+                        let stream = stream
+                            .eventsource()
+                            .with_last_event_id(this.last_event_id.clone())
+                            .with_limits(this.options.limits)
+                            .with_utf8_policy(this.options.utf8_policy)
+                            .with_flush_on_eof(this.options.flush_on_eof);
+                        this.state.set(State::Streaming(stream));
+                        continue;
+                    }
+                    Err(err) => Some(ReconnectError::Connect(err)),
+                },
+                StateProj::Streaming(mut stream) => {
+                    let item = ready!(stream.as_mut().poll_next(cx));
+                    this.last_event_id.clear();
+                    this.last_event_id.push_str(stream.last_event_id());
+                    if let Some(retry) = stream.retry() {
+                        *this.retry = retry;
+                    }
+                    match item {
+                        Some(Ok(event)) => return Poll::Ready(Some(Ok(event))),
+                        Some(Err(EventStreamError::Transport(err))) => {
+                            Some(ReconnectError::Stream(EventStreamError::Transport(err)))
+                        }
+                        Some(Err(err)) => {
+                            return Poll::Ready(Some(Err(ReconnectError::Stream(err))))
+                        }
+                        None => None,
+                    }
+                }
+                StateProj::Waiting(sleeping) => {
+                    ready!(sleeping.poll(cx));
+                    let connecting = (this.connect)(this.last_event_id);
+                    this.state.set(State::Connecting(connecting));
+                    continue;
+                }
+                StateProj::Done => return Poll::Ready(None),
+            };
+
+            if matches!(this.options.max_retries, Some(max) if *this.attempts >= max) {
+                this.state.set(State::Done);
+            } else {
+                let delay = this.options.delay(*this.retry, *this.attempts, this.rng);
+                *this.attempts += 1;
+                this.state.set(State::Waiting((this.sleep)(delay)));
+            }
+            if let Some(err) = err {
+                return Poll::Ready(Some(Err(err)));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::limits::{Limit, LimitAction};
+    use futures::prelude::*;
+    use std::sync::{Arc, Mutex};
+
+    #[tokio::test]
+    async fn reconnect() {
+        let connects = Arc::new(Mutex::new(Vec::new()));
+        let delays = Arc::new(Mutex::new(Vec::new()));
+        let mut responses = vec![
+            Ok(vec!["retry: 100\nid: 1\ndata: a\n\n"]),
+            Err("refused"),
+            Ok(vec![]),
+            Ok(vec!["data: b\n\nid: 2\ndata: c\n\nid: 3\n\n"]),
+        ]
+        .into_iter();
+
+        let connects_ = connects.clone();
+        let delays_ = delays.clone();
+        let stream = ReconnectingEventStream::with_options(
+            move |last_event_id: &str| {
+                connects_.lock().unwrap().push(last_event_id.to_string());
+                future::ready(
+                    responses
+                        .next()
+                        .unwrap_or(Err("done"))
+                        .map(|chunks| stream::iter(chunks.into_iter().map(Ok::<_, ()>))),
+                )
+            },
+            move |delay| {
+                delays_.lock().unwrap().push(delay);
+                future::ready(())
+            },
+            ReconnectOptions {
+                jitter: 0.0,
+                max_retries: Some(3),
+                ..Default::default()
+            },
+        );
+        let results = stream.collect::<Vec<_>>().await;
+        let event = |id: &str, data: &str, retry: Option<u64>| {
+            Ok(Event {
+                id: id.to_string(),
+                data: data.to_string(),
+                retry: retry.map(Duration::from_millis),
+                ..Default::default()
+            })
+        };
+        assert_eq!(
+            results,
+            vec![
+                event("1", "a", Some(100)),
+                Err(ReconnectError::Connect("refused")),
+                event("1", "b", None),
+                event("2", "c", None),
+                Err(ReconnectError::Connect("done")),
+                Err(ReconnectError::Connect("done")),
+                Err(ReconnectError::Connect("done")),
+            ]
+        );
+        assert_eq!(
+            *connects.lock().unwrap(),
+            vec!["", "1", "1", "1", "3", "3", "3"]
+        );
+        assert_eq!(
+            *delays.lock().unwrap(),
+            [100, 200, 100, 100, 200, 400]
+                .iter()
+                .map(|ms| Duration::from_millis(*ms))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[tokio::test]
+    async fn parser_options() {
+        let stream = ReconnectingEventStream::with_options(
+            |_: &str| {
+                future::ready(Ok::<_, ()>(stream::iter(vec![Ok::<_, ()>(
+                    "data: 0123456789\n\ndata: a\n\ndata: b",
+                )])))
+            },
+            |_| future::ready(()),
+            ReconnectOptions {
+                max_retries: Some(1),
+                limits: Limits {
+                    max_line_length: Some(8),
+                    on_exceeded: LimitAction::Skip,
+                    ..Default::default()
+                },
+                flush_on_eof: true,
+                ..Default::default()
+            },
+        );
+        let results = stream
+            .map(|result| result.map(|event| event.data))
+            .take(6)
+            .collect::<Vec<_>>()
+            .await;
+        let connection = || {
+            vec![
+                Err(ReconnectError::Stream(EventStreamError::LimitExceeded(
+                    Limit::LineLength,
+                ))),
+                Ok("a".to_string()),
+                Ok("b".to_string()),
+            ]
+        };
+        assert_eq!(
+            results,
+            connection()
+                .into_iter()
+                .chain(connection())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn delay() {
+        let mut rng = 0x2545_f491_4f6c_dd1d;
+        let options = ReconnectOptions::default();
+        let retry = Duration::from_secs(1);
+        for attempts in 0..4 {
+            let delay = options.delay(retry, attempts, &mut rng);
+            let max = retry * 2u32.pow(attempts as u32);
+            assert!(delay <= max && delay >= max.mul_f64(0.8), "{:?}", delay);
+        }
+        assert_eq!(
+            ReconnectOptions {
+                jitter: 0.0,
+                ..options
+            }
+            .delay(retry, 10, &mut rng),
+            options.max_delay
+        );
+    }
+
+    #[test]
+    fn delay_overflow() {
+        let mut rng = 0x2545_f491_4f6c_dd1d;
+        let retry = Duration::from_secs(1);
+        let options = ReconnectOptions {
+            max_delay: Duration::MAX,
+            ..Default::default()
+        };
+        for attempts in [0, 64, 1024, usize::MAX] {
+            assert!(options.delay(retry, attempts, &mut rng) >= retry.mul_f64(0.8));
+        }
+        assert_eq!(
+            ReconnectOptions {
+                jitter: 0.0,
+                ..options
+            }
+            .delay(retry, 1024, &mut rng),
+            Duration::MAX
+        );
+        let options = ReconnectOptions {
+            backoff: f64::NAN,
+            jitter: f64::NAN,
+            ..options
+        };
+        for attempts in 0..4 {
+            assert_eq!(options.delay(retry, attempts, &mut rng), retry);
+        }
+        assert_eq!(
+            ReconnectOptions {
+                backoff: f64::INFINITY,
+                jitter: f64::INFINITY,
+                ..options
+            }
+            .delay(retry, 3, &mut rng),
+            retry
+        );
+    }
+}
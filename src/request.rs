@@ -0,0 +1,256 @@
+use crate::event_stream::EventStream;
+use crate::mime::{is_event_stream, EVENT_STREAM};
+use bytes::Bytes;
+use core::fmt;
+use core::future::Future;
+use core::pin::Pin;
+use futures_core::ready;
+use futures_core::stream::Stream;
+use futures_core::task::{Context, Poll};
+use reqwest::header::{HeaderValue, ACCEPT, CACHE_CONTROL, CONTENT_TYPE};
+use reqwest::{RequestBuilder, Response, StatusCode};
+
+/// The stream of body chunks of a [`Response`]
+pub type ResponseBytes = Pin<Box<dyn Stream<Item = reqwest::Result<Bytes>> + Send>>;
+
+type Pending = Pin<Box<dyn Future<Output = reqwest::Result<Response>> + Send>>;
+
+/// Error returned by an [`EventsourceRequest`]
+#[derive(Debug)]
+pub enum RequestError {
+    /// The request could not be sent
+    Transport(reqwest::Error),
+    /// The response status is not `200 OK`
+    InvalidStatus(Response),
+    /// The `Content-Type` of the response is not `text/event-stream`
+    InvalidContentType(Response),
+}
+
+impl fmt::Display for RequestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Transport(err) => f.write_fmt(format_args!("Transport error: {}", err)),
+            Self::InvalidStatus(response) => {
+                f.write_fmt(format_args!("Invalid status: {}", response.status()))
+            }
+            Self::InvalidContentType(response) => f.write_fmt(format_args!(
+                "Invalid content type: {:?}",
+                response.headers().get(CONTENT_TYPE)
+            )),
+        }
+    }
+}
+
+impl std::error::Error for RequestError {}
+
+impl From<reqwest::Error> for RequestError {
+    fn from(err: reqwest::Error) -> Self {
+        Self::Transport(err)
+    }
+}
+
+/// Extension of [`RequestBuilder`] to open an [`EventStream`]
+pub trait RequestBuilderExt {
+    /// Send the request as an Eventsource, see [`EventsourceRequest`]
+    fn eventsource(self) -> EventsourceRequest;
+}
+
+impl RequestBuilderExt for RequestBuilder {
+    fn eventsource(self) -> EventsourceRequest {
+        EventsourceRequest::new(self)
+    }
+}
+
+/// A future which sends a request with the headers of an Eventsource and resolves to an
+/// [`EventStream`] of the response
+///
+/// It sets `Accept: text/event-stream` and `Cache-Control: no-cache` unless the request already
+/// has these headers, and `Last-Event-ID` if given. The response must have status `200 OK` and a
+/// `text/event-stream` content type.
+///
+/// ```no_run
+/// # use eventsource_stream::RequestBuilderExt;
+/// # async fn run() -> Result<(), eventsource_stream::RequestError> {
+/// let stream = reqwest::Client::new()
+///     .get("http://localhost:7020/notifications")
+///     .eventsource()
+///     .with_last_event_id("42")
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct EventsourceRequest {
+    builder: Option<RequestBuilder>,
+    last_event_id: String,
+    pending: Option<Pending>,
+}
+
+impl EventsourceRequest {
+    /// Send the request of `builder` as an Eventsource
+    pub fn new(builder: RequestBuilder) -> Self {
+        Self {
+            builder: Some(builder),
+            last_event_id: String::new(),
+            pending: None,
+        }
+    }
+
+    /// Send `id` as `Last-Event-ID` header and use it as the last event ID of the stream
+    pub fn with_last_event_id(mut self, id: impl Into<String>) -> Self {
+        self.last_event_id = id.into();
+        self
+    }
+}
+
+impl Future for EventsourceRequest {
+    type Output = Result<EventStream<ResponseBytes>, RequestError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let this = &mut *self;
+        if let Some(builder) = this.builder.take() {
+            let builder = if this.last_event_id.is_empty() {
+                builder
+            } else {
+                builder.header("Last-Event-ID", this.last_event_id.as_str())
+            };
+            let (client, request) = builder.build_split();
+            this.pending = Some(Box::pin(async move {
+                let mut request = request?;
+                // Appending would send a second value next to the one set by the caller
+                let headers = request.headers_mut();
+                headers
+                    .entry(ACCEPT)
+                    .or_insert(HeaderValue::from_static(EVENT_STREAM));
+                headers
+                    .entry(CACHE_CONTROL)
+                    .or_insert(HeaderValue::from_static("no-cache"));
+                client.execute(request).await
+            }));
+        }
+        let pending = this
+            .pending
+            .as_mut()
+            .expect("EventsourceRequest polled after completion");
+        let response = ready!(pending.as_mut().poll(cx));
+        this.pending = None;
+        let response = response?;
+        if response.status() != StatusCode::OK {
+            return Poll::Ready(Err(RequestError::InvalidStatus(response)));
+        }
+        let is_event_stream = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .map(|content_type| is_event_stream(content_type.as_bytes()))
+            .unwrap_or(false);
+        if !is_event_stream {
+            return Poll::Ready(Err(RequestError::InvalidContentType(response)));
+        }
+        let bytes: ResponseBytes = Box::pin(response.bytes_stream());
+        Poll::Ready(Ok(
+            EventStream::new(bytes).with_last_event_id(core::mem::take(&mut this.last_event_id))
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::prelude::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Serve a single connection with `response`, returning the request head
+    async fn serve(response: &'static str) -> (String, tokio::task::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let handle = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buffer = [0; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                let n = socket.read(&mut buffer).await.unwrap();
+                request.extend_from_slice(&buffer[..n]);
+            }
+            socket.write_all(response.as_bytes()).await.unwrap();
+            String::from_utf8(request).unwrap().to_lowercase()
+        });
+        (url, handle)
+    }
+
+    #[tokio::test]
+    async fn request() {
+        let (url, handle) = serve(concat!(
+            "HTTP/1.1 200 OK\r\n",
+            "Content-Type: text/event-stream; charset=utf-8\r\n",
+            "Content-Length: 18\r\n",
+            "\r\n",
+            "data: a\n\ndata: b\n\n",
+        ))
+        .await;
+        let events = reqwest::Client::new()
+            .get(url)
+            .eventsource()
+            .with_last_event_id("41")
+            .await
+            .unwrap()
+            .map_ok(|event| (event.data, event.id))
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(
+            events,
+            vec![
+                ("a".to_string(), "41".to_string()),
+                ("b".to_string(), "41".to_string())
+            ]
+        );
+        let request = handle.await.unwrap();
+        assert!(request.contains("accept: text/event-stream\r\n"));
+        assert!(request.contains("cache-control: no-cache\r\n"));
+        assert!(request.contains("last-event-id: 41\r\n"));
+    }
+
+    #[tokio::test]
+    async fn keep_headers() {
+        let (url, handle) = serve(concat!(
+            "HTTP/1.1 200 OK\r\n",
+            "Content-Type: text/event-stream\r\n",
+            "Content-Length: 0\r\n",
+            "\r\n",
+        ))
+        .await;
+        reqwest::Client::new()
+            .get(url)
+            .header(ACCEPT, "text/event-stream, */*")
+            .header(CACHE_CONTROL, "no-store")
+            .eventsource()
+            .await
+            .unwrap();
+        let request = handle.await.unwrap();
+        assert_eq!(request.matches("accept:").count(), 1);
+        assert!(request.contains("accept: text/event-stream, */*\r\n"));
+        assert_eq!(request.matches("cache-control:").count(), 1);
+        assert!(request.contains("cache-control: no-store\r\n"));
+    }
+
+    #[tokio::test]
+    async fn invalid_response() {
+        let (url, _) = serve("HTTP/1.1 204 No Content\r\n\r\n").await;
+        let result = reqwest::Client::new().get(url).eventsource().await;
+        assert!(
+            matches!(result, Err(RequestError::InvalidStatus(response)) if response.status() == 204)
+        );
+
+        let (url, handle) = serve(concat!(
+            "HTTP/1.1 200 OK\r\n",
+            "Content-Type: application/json\r\n",
+            "Content-Length: 2\r\n",
+            "\r\n",
+            "{}",
+        ))
+        .await;
+        let result = reqwest::Client::new().get(url).eventsource().await;
+        assert!(matches!(result, Err(RequestError::InvalidContentType(_))));
+        assert!(!handle.await.unwrap().contains("last-event-id"));
+    }
+}
@@ -0,0 +1,405 @@
+#[cfg(not(feature = "std"))]
+use alloc::{boxed::Box, collections::BTreeMap, string::String, vec::Vec};
+#[cfg(feature = "std")]
+use std::collections::{BTreeMap, VecDeque};
+#[cfg(feature = "std")]
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use crate::event::Event;
+use crate::event_stream::EventStream;
+use core::future::Future;
+use core::pin::Pin;
+use futures_core::ready;
+use futures_core::stream::{Stream, TryStream};
+#[cfg(feature = "std")]
+use futures_core::task::Waker;
+use futures_core::task::{Context, Poll};
+use pin_project::pin_project;
+
+type Handler = Box<dyn FnMut(&Event) + Send>;
+type ErrorHandler<E> = Box<dyn FnMut(E) + Send>;
+
+/// Calls handlers registered for the type of every event of a stream, like `addEventListener`
+/// of the browser `EventSource`
+///
+/// Events without an `event` field have the type `message`. Events without a handler for their
+/// type are passed to the fallback, if any. The router is a future which completes when the
+/// stream ends. Errors of the stream are passed to the error handler and routing goes on, but
+/// without an error handler the router completes with the first error.
+///
+/// ```
+/// # use eventsource_stream::{EventRouter, Eventsource};
+/// # futures::executor::block_on(async {
+/// let stream = futures::stream::iter(vec![Ok::<_, ()>("event: add\ndata: 1\n\ndata: 2\n\n")]);
+/// let result = stream
+///     .eventsource()
+///     .router()
+///     .on("add", |event| println!("add {}", event.data))
+///     .on("message", |event| println!("message {}", event.data))
+///     .fallback(|event| println!("unknown {}", event.event))
+///     .await;
+/// assert!(result.is_ok());
+/// # });
+/// ```
+#[pin_project]
+pub struct EventRouter<S: TryStream> {
+    #[pin]
+    stream: S,
+    routes: BTreeMap<String, Vec<Handler>>,
+    fallback: Option<Handler>,
+    on_error: Option<ErrorHandler<S::Error>>,
+}
+
+impl<S: TryStream> EventRouter<S> {
+    /// Create a router for the events of `stream` without any handlers
+    pub fn new(stream: S) -> Self {
+        Self {
+            stream,
+            routes: BTreeMap::new(),
+            fallback: None,
+            on_error: None,
+        }
+    }
+
+    /// Call `handler` for every event of type `event_type`. Several handlers of the same type are
+    /// called in the order they were registered.
+    pub fn on<F>(mut self, event_type: impl Into<String>, handler: F) -> Self
+    where
+        F: FnMut(&Event) + Send + 'static,
+    {
+        self.routes
+            .entry(event_type.into())
+            .or_default()
+            .push(Box::new(handler));
+        self
+    }
+
+    /// Call `handler` for every event whose type has no handler, replacing the previous fallback
+    pub fn fallback<F>(mut self, handler: F) -> Self
+    where
+        F: FnMut(&Event) + Send + 'static,
+    {
+        self.fallback = Some(Box::new(handler));
+        self
+    }
+
+    /// Call `handler` for every error of the stream and keep routing, replacing the previous
+    /// error handler
+    pub fn on_error<F>(mut self, handler: F) -> Self
+    where
+        F: FnMut(S::Error) + Send + 'static,
+    {
+        self.on_error = Some(Box::new(handler));
+        self
+    }
+
+    /// Get a stream of the events of type `event_type`, which counts as a handler of the type
+    ///
+    /// Events are queued until they are taken, and the stream ends once the router is dropped.
+    /// Dropping the stream stops the queueing.
+    ///
+    /// ```
+    /// # use eventsource_stream::Eventsource;
+    /// # use futures::stream::StreamExt;
+    /// # futures::executor::block_on(async {
+    /// let stream = futures::stream::iter(vec![Ok::<_, ()>("event: add\ndata: 1\n\n")]);
+    /// let mut router = stream.eventsource().router();
+    /// let adds = router.stream("add");
+    /// router.await.unwrap();
+    /// assert_eq!(adds.map(|event| event.data).collect::<Vec<_>>().await, vec!["1"]);
+    /// # });
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn stream(&mut self, event_type: impl Into<String>) -> RouteStream {
+        let queue = Arc::new(Mutex::new(Queue {
+            events: VecDeque::new(),
+            waker: None,
+            is_closed: false,
+        }));
+        let sender = Sender(queue.clone());
+        self.routes
+            .entry(event_type.into())
+            .or_default()
+            .push(Box::new(move |event| sender.send(event)));
+        RouteStream { queue }
+    }
+
+    /// Call the handlers for `event`, returning whether any handler was called
+    pub fn route(&mut self, event: &Event) -> bool {
+        route(&mut self.routes, &mut self.fallback, event)
+    }
+}
+
+impl<S> EventStream<S>
+where
+    Self: TryStream,
+{
+    /// Route the events of the stream to handlers by their type, see [`EventRouter`]
+    pub fn router(self) -> EventRouter<Self> {
+        EventRouter::new(self)
+    }
+}
+
+fn route(
+    routes: &mut BTreeMap<String, Vec<Handler>>,
+    fallback: &mut Option<Handler>,
+    event: &Event,
+) -> bool {
+    let event_type = if event.event.is_empty() {
+        "message"
+    } else {
+        event.event.as_str()
+    };
+    let handlers = match routes.get_mut(event_type) {
+        Some(handlers) => handlers,
+        None => match fallback {
+            Some(fallback) => {
+                fallback(event);
+                return true;
+            }
+            None => return false,
+        },
+    };
+    for handler in handlers.iter_mut() {
+        handler(event);
+    }
+    true
+}
+
+impl<S, E> Future for EventRouter<S>
+where
+    S: Stream<Item = Result<Event, E>>,
+{
+    type Output = Result<(), E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let mut this = self.project();
+        loop {
+            match ready!(this.stream.as_mut().poll_next(cx)) {
+                Some(Ok(event)) => {
+                    route(this.routes, this.fallback, &event);
+                }
+                Some(Err(err)) => match this.on_error {
+                    Some(on_error) => on_error(err),
+                    None => return Poll::Ready(Err(err)),
+                },
+                None => return Poll::Ready(Ok(())),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[derive(Debug)]
+struct Queue {
+    events: VecDeque<Event>,
+    waker: Option<Waker>,
+    // Whether the router was dropped
+    is_closed: bool,
+}
+
+#[cfg(feature = "std")]
+fn lock(queue: &Mutex<Queue>) -> MutexGuard<'_, Queue> {
+    queue.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+// The handler side of a `RouteStream`, which closes it when the router drops its handlers
+#[cfg(feature = "std")]
+struct Sender(Arc<Mutex<Queue>>);
+
+#[cfg(feature = "std")]
+impl Sender {
+    fn send(&self, event: &Event) {
+        if Arc::strong_count(&self.0) == 1 {
+            return;
+        }
+        let mut queue = lock(&self.0);
+        queue.events.push_back(event.clone());
+        if let Some(waker) = queue.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Drop for Sender {
+    fn drop(&mut self) {
+        let mut queue = lock(&self.0);
+        queue.is_closed = true;
+        if let Some(waker) = queue.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// A Stream of the events of one type of an [`EventRouter`], see [`EventRouter::stream`]
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct RouteStream {
+    queue: Arc<Mutex<Queue>>,
+}
+
+#[cfg(feature = "std")]
+impl Stream for RouteStream {
+    type Item = Event;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        let mut queue = lock(&self.queue);
+        if let Some(event) = queue.events.pop_front() {
+            return Poll::Ready(Some(event));
+        }
+        if queue.is_closed {
+            return Poll::Ready(None);
+        }
+        queue.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_stream::EventStreamError;
+    use crate::limits::{Limit, LimitAction, Limits};
+    use crate::Eventsource;
+    use futures::prelude::*;
+    use std::sync::{Arc, Mutex};
+
+    #[tokio::test]
+    async fn route_events() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let record = |name: &'static str| {
+            let calls = calls.clone();
+            move |event: &Event| calls.lock().unwrap().push((name, event.data.clone()))
+        };
+        let input =
+            "event: add\ndata: 1\n\ndata: 2\n\nevent: remove\ndata: 3\n\nevent: add\ndata: 4\n\n";
+        futures::stream::iter(vec![Ok::<_, ()>(input)])
+            .eventsource()
+            .router()
+            .on("add", record("add"))
+            .on("message", record("message"))
+            .on("add", record("add again"))
+            .fallback(record("fallback"))
+            .await
+            .unwrap();
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec![
+                ("add", "1".to_string()),
+                ("add again", "1".to_string()),
+                ("message", "2".to_string()),
+                ("fallback", "3".to_string()),
+                ("add", "4".to_string()),
+                ("add again", "4".to_string()),
+            ]
+        );
+
+        let mut router =
+            EventRouter::new(futures::stream::empty::<Result<Event, ()>>()).on("add", |_| {});
+        assert!(router.route(&Event {
+            event: "add".to_string(),
+            ..Default::default()
+        }));
+        assert!(!router.route(&Event::default()));
+    }
+
+    #[tokio::test]
+    async fn route_after_error() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        let events_ = events.clone();
+        let errors_ = errors.clone();
+        futures::stream::iter(vec![Ok::<_, ()>("data: 0123456789\n\ndata: ok\n\n")])
+            .eventsource()
+            .with_limits(Limits {
+                max_line_length: Some(8),
+                on_exceeded: LimitAction::Skip,
+                ..Default::default()
+            })
+            .router()
+            .on("message", move |event| {
+                events_.lock().unwrap().push(event.data.clone())
+            })
+            .on_error(move |err| errors_.lock().unwrap().push(err))
+            .await
+            .unwrap();
+        assert_eq!(*events.lock().unwrap(), vec!["ok".to_string()]);
+        assert_eq!(
+            *errors.lock().unwrap(),
+            vec![EventStreamError::LimitExceeded(Limit::LineLength)]
+        );
+
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        let errors_ = errors.clone();
+        EventRouter::new(futures::stream::iter(vec![
+            Ok(Event::default()),
+            Err("closed"),
+            Ok(Event::default()),
+        ]))
+        .on_error(move |err| errors_.lock().unwrap().push(err))
+        .await
+        .unwrap();
+        assert_eq!(*errors.lock().unwrap(), vec!["closed"]);
+    }
+
+    #[tokio::test]
+    async fn end_on_error() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let events_ = events.clone();
+        let result = futures::stream::iter(vec![Ok::<_, ()>("data: 0123456789\n\ndata: ok\n\n")])
+            .eventsource()
+            .with_limits(Limits {
+                max_line_length: Some(8),
+                on_exceeded: LimitAction::Skip,
+                ..Default::default()
+            })
+            .router()
+            .on("message", move |event| {
+                events_.lock().unwrap().push(event.data.clone())
+            })
+            .await;
+        assert_eq!(
+            result,
+            Err(EventStreamError::LimitExceeded(Limit::LineLength))
+        );
+        assert!(events.lock().unwrap().is_empty());
+
+        let result = EventRouter::new(futures::stream::iter(vec![
+            Ok(Event::default()),
+            Err("closed"),
+        ]))
+        .await;
+        assert_eq!(result, Err("closed"));
+    }
+
+    #[tokio::test]
+    async fn route_streams() {
+        let (sender, receiver) = futures::channel::mpsc::unbounded::<Result<_, ()>>();
+        let mut router = receiver.eventsource().router();
+        let adds = router.stream("add");
+        let messages = router.stream("message");
+        let fallback = Arc::new(Mutex::new(Vec::new()));
+        let fallback_ = fallback.clone();
+        let router =
+            router.fallback(move |event| fallback_.lock().unwrap().push(event.data.clone()));
+        drop(messages);
+        let router = tokio::spawn(router);
+
+        let mut adds = adds.map(|event| event.data);
+        sender
+            .unbounded_send(Ok("event: add\ndata: 1\n\n"))
+            .unwrap();
+        assert_eq!(adds.next().await, Some("1".to_string()));
+        sender
+            .unbounded_send(Ok(
+                "data: 2\n\nevent: other\ndata: 3\n\nevent: add\ndata: 4\n\n",
+            ))
+            .unwrap();
+        assert_eq!(adds.next().await, Some("4".to_string()));
+        drop(sender);
+        assert_eq!(adds.next().await, None);
+        router.await.unwrap().unwrap();
+        assert_eq!(*fallback.lock().unwrap(), vec!["3".to_string()]);
+    }
+}
@@ -1,10 +1,24 @@
 use crate::event_stream::EventStream;
+#[cfg(feature = "futures-io")]
+use crate::reader::AsyncReadChunks;
+#[cfg(feature = "tokio")]
+use crate::reader::TokioReadChunks;
 use futures_core::stream::Stream;
 
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+
 /// Main entrypoint for creating [`crate::Event`] streams
 pub trait Eventsource: Sized {
     /// Create an event stream from a stream of bytes
     fn eventsource(self) -> EventStream<Self>;
+
+    /// Create an event stream from a stream of bytes which resumes after `last_event_id`, see
+    /// [`EventStream::with_last_event_id`]. Further options can be chained on the returned
+    /// stream, such as [`EventStream::with_retry`].
+    fn eventsource_from(self, last_event_id: impl Into<String>) -> EventStream<Self> {
+        self.eventsource().with_last_event_id(last_event_id)
+    }
 }
 
 impl<S, B, E> Eventsource for S
@@ -16,3 +30,49 @@ where
         EventStream::new(self)
     }
 }
+
+/// Size of the read buffer used by [`AsyncReadEventsource::read_eventsource`] and
+/// [`TokioEventsource::tokio_eventsource`]
+#[cfg(any(feature = "tokio", feature = "futures-io"))]
+const READ_CAPACITY: usize = 8 * 1024;
+
+/// Create [`crate::Event`] streams from a `futures_io::AsyncRead`, such as a pipe or a socket
+#[cfg(feature = "futures-io")]
+pub trait AsyncReadEventsource: futures_io::AsyncRead + Sized {
+    /// Create an event stream reading chunks of up to 8 KiB
+    fn read_eventsource(self) -> EventStream<AsyncReadChunks<Self>> {
+        self.read_eventsource_with_capacity(READ_CAPACITY)
+    }
+
+    /// Create an event stream reading chunks of up to `capacity` bytes
+    fn read_eventsource_with_capacity(self, capacity: usize) -> EventStream<AsyncReadChunks<Self>> {
+        EventStream::new(AsyncReadChunks::new(self, capacity))
+    }
+}
+
+#[cfg(feature = "futures-io")]
+impl<R: futures_io::AsyncRead> AsyncReadEventsource for R {}
+
+/// Create [`crate::Event`] streams from a `tokio::io::AsyncRead`, such as a child process' stdout
+/// or a unix socket
+///
+/// The methods are named apart from [`AsyncReadEventsource`], as types like `&[u8]` implement
+/// both `AsyncRead` traits.
+#[cfg(feature = "tokio")]
+pub trait TokioEventsource: tokio::io::AsyncRead + Sized {
+    /// Create an event stream reading chunks of up to 8 KiB
+    fn tokio_eventsource(self) -> EventStream<TokioReadChunks<Self>> {
+        self.tokio_eventsource_with_capacity(READ_CAPACITY)
+    }
+
+    /// Create an event stream reading chunks of up to `capacity` bytes
+    fn tokio_eventsource_with_capacity(
+        self,
+        capacity: usize,
+    ) -> EventStream<TokioReadChunks<Self>> {
+        EventStream::new(TokioReadChunks::new(self, capacity))
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<R: tokio::io::AsyncRead> TokioEventsource for R {}
@@ -0,0 +1,309 @@
+#[cfg(not(feature = "std"))]
+use alloc::{
+    string::{FromUtf8Error, String},
+    vec::Vec,
+};
+
+#[cfg(feature = "std")]
+use std::string::FromUtf8Error;
+
+/// How invalid UTF-8 in the source stream is handled
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub enum Utf8Policy {
+    /// End the input before the line which contains invalid bytes, and return an error after
+    /// the events before it
+    Strict,
+    /// Replace invalid bytes with U+FFFD REPLACEMENT CHARACTER, as required by the HTML spec
+    #[default]
+    Lossy,
+    /// Drop every line which contains invalid bytes
+    SkipLine,
+}
+
+/// Decodes chunks of bytes, carrying incomplete UTF-8 sequences over to the next chunk
+#[derive(Debug, Default)]
+pub struct Utf8Decoder {
+    buffer: Vec<u8>,
+    policy: Utf8Policy,
+    skipping: bool,
+    skip_lf: bool,
+}
+
+impl Utf8Decoder {
+    pub fn set_policy(&mut self, policy: Utf8Policy) {
+        self.policy = policy;
+    }
+
+    /// Decode the next chunk. Under [`Utf8Policy::Strict`] invalid bytes are returned as an
+    /// error, whose bytes are valid up to [`core::str::Utf8Error::valid_up_to`], and the held
+    /// bytes are dropped.
+    pub fn decode(&mut self, bytes: &[u8]) -> Result<String, FromUtf8Error> {
+        match self.policy {
+            Utf8Policy::Strict => {
+                self.buffer.extend_from_slice(bytes);
+                decode_strict(&mut self.buffer)
+            }
+            Utf8Policy::Lossy => {
+                self.buffer.extend_from_slice(bytes);
+                Ok(decode_lossy(&mut self.buffer))
+            }
+            Utf8Policy::SkipLine => {
+                let has_eol = bytes.iter().any(is_eol);
+                self.buffer.extend_from_slice(bytes);
+                // The incomplete line is held as is until its terminator arrives, instead of
+                // being validated again with every chunk
+                if !has_eol && !self.skipping {
+                    return Ok(String::new());
+                }
+                Ok(decode_skip_line(
+                    &mut self.buffer,
+                    &mut self.skipping,
+                    &mut self.skip_lf,
+                ))
+            }
+        }
+    }
+
+    /// Get the number of bytes held back until more input arrives
+    pub fn held_len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Drop the held bytes, which start a line that is not decoded
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+        self.skipping = false;
+        self.skip_lf = false;
+    }
+
+    /// Decode the bytes left over at the end of the source
+    pub fn finish(&mut self) -> Option<Result<String, FromUtf8Error>> {
+        if self.buffer.is_empty() {
+            return None;
+        }
+        let bytes = core::mem::take(&mut self.buffer);
+        match self.policy {
+            Utf8Policy::Strict => Some(String::from_utf8(bytes)),
+            Utf8Policy::Lossy => Some(Ok(String::from_utf8_lossy(&bytes).into_owned())),
+            Utf8Policy::SkipLine => match String::from_utf8(bytes) {
+                Ok(string) if !self.skipping => Some(Ok(string)),
+                _ => None,
+            },
+        }
+    }
+}
+
+#[inline]
+fn is_eol(b: &u8) -> bool {
+    *b == b'\n' || *b == b'\r'
+}
+
+fn decode_strict(buffer: &mut Vec<u8>) -> Result<String, FromUtf8Error> {
+    let bytes = core::mem::take(buffer);
+    match String::from_utf8(bytes) {
+        Ok(string) => Ok(string),
+        Err(err) if err.utf8_error().error_len().is_some() => Err(err),
+        Err(err) => {
+            let valid_size = err.utf8_error().valid_up_to();
+            let mut bytes = err.into_bytes();
+            let rem = bytes.split_off(valid_size);
+            *buffer = rem;
+            Ok(unsafe { String::from_utf8_unchecked(bytes) })
+        }
+    }
+}
+
+fn decode_lossy(buffer: &mut Vec<u8>) -> String {
+    let mut string = String::new();
+    let mut start = 0;
+    loop {
+        match core::str::from_utf8(&buffer[start..]) {
+            Ok(valid) => {
+                string.push_str(valid);
+                buffer.clear();
+                return string;
+            }
+            Err(err) => {
+                let valid_end = start + err.valid_up_to();
+                string
+                    .push_str(unsafe { core::str::from_utf8_unchecked(&buffer[start..valid_end]) });
+                match err.error_len() {
+                    Some(len) => {
+                        string.push(char::REPLACEMENT_CHARACTER);
+                        start = valid_end + len;
+                    }
+                    None => {
+                        buffer.drain(..valid_end);
+                        return string;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Only complete lines are decoded so that a line with invalid bytes can be dropped as a whole.
+fn decode_skip_line(buffer: &mut Vec<u8>, skipping: &mut bool, skip_lf: &mut bool) -> String {
+    let mut string = String::new();
+    loop {
+        if buffer.is_empty() {
+            return string;
+        }
+        if *skip_lf {
+            *skip_lf = false;
+            if buffer[0] == b'\n' {
+                buffer.drain(..1);
+                continue;
+            }
+        }
+        if *skipping {
+            match buffer.iter().position(is_eol) {
+                Some(pos) => {
+                    *skipping = false;
+                    *skip_lf = buffer[pos] == b'\r';
+                    buffer.drain(..=pos);
+                    continue;
+                }
+                None => {
+                    buffer.clear();
+                    return string;
+                }
+            }
+        }
+        let (valid_size, is_invalid) = match core::str::from_utf8(buffer) {
+            Ok(_) => (buffer.len(), false),
+            Err(err) => (err.valid_up_to(), err.error_len().is_some()),
+        };
+        let line_end = buffer[..valid_size]
+            .iter()
+            .rposition(is_eol)
+            .map_or(0, |pos| pos + 1);
+        string.push_str(unsafe { core::str::from_utf8_unchecked(&buffer[..line_end]) });
+        buffer.drain(..line_end);
+        if !is_invalid {
+            return string;
+        }
+        *skipping = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Decode every chunk and the end of the source, like the parser does
+    fn decode<B: AsRef<[u8]>>(
+        chunks: Vec<B>,
+        policy: Utf8Policy,
+    ) -> Vec<Result<String, FromUtf8Error>> {
+        let mut decoder = Utf8Decoder::default();
+        decoder.set_policy(policy);
+        let mut results = chunks
+            .iter()
+            .map(|chunk| decoder.decode(chunk.as_ref()))
+            .collect::<Vec<_>>();
+        results.extend(decoder.finish());
+        results
+    }
+
+    fn decode_all<B: AsRef<[u8]>>(chunks: Vec<B>, policy: Utf8Policy) -> Vec<String> {
+        decode(chunks, policy)
+            .into_iter()
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn valid_streams() {
+        assert_eq!(
+            decode_all(vec![b"Hello, world!"], Utf8Policy::Lossy),
+            vec!["Hello, world!"]
+        );
+        assert_eq!(
+            decode_all(vec!["Hello, world!"], Utf8Policy::Lossy),
+            vec!["Hello, world!"]
+        );
+        assert_eq!(decode_all(vec![""], Utf8Policy::Lossy), vec![""]);
+        assert_eq!(
+            decode_all(vec!["Hello", ", world!"], Utf8Policy::Lossy),
+            vec!["Hello", ", world!"]
+        );
+        assert_eq!(
+            decode_all(vec![vec![240, 159, 145, 141]], Utf8Policy::Lossy),
+            vec!["👍"]
+        );
+        assert_eq!(
+            decode_all(vec![vec![240, 159], vec![145, 141]], Utf8Policy::Lossy),
+            vec!["", "👍"]
+        );
+        assert_eq!(
+            decode_all(
+                vec![vec![240, 159], vec![145, 141, 240, 159, 145, 141]],
+                Utf8Policy::Lossy
+            ),
+            vec!["", "👍👍"]
+        );
+    }
+
+    #[test]
+    fn invalid_streams() {
+        let results = decode(vec![vec![240, 159]], Utf8Policy::Strict);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0], Ok("".to_string()));
+        assert!(results[1].is_err());
+
+        let results = decode(
+            vec![vec![240, 159], vec![145, 141, 240, 159, 145]],
+            Utf8Policy::Strict,
+        );
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0], Ok("".to_string()));
+        assert_eq!(results[1], Ok("👍".to_string()));
+        assert!(results[2].is_err());
+
+        let results = decode(vec![&b"a\n"[..], b"b\xffc\n", b"d\n"], Utf8Policy::Strict);
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0], Ok("a\n".to_string()));
+        let err = results[1].as_ref().unwrap_err();
+        assert_eq!(err.utf8_error().valid_up_to(), 1);
+        assert_eq!(results[2], Ok("d\n".to_string()));
+    }
+
+    #[test]
+    fn lossy_streams() {
+        assert_eq!(
+            decode_all(
+                vec![b"a\xffb".to_vec(), vec![240, 159], vec![145, 141, 240, 159]],
+                Utf8Policy::Lossy
+            ),
+            vec!["a\u{fffd}b", "", "👍", "\u{fffd}"]
+        );
+    }
+
+    #[test]
+    fn skip_line_streams() {
+        assert_eq!(
+            decode_all(
+                vec![
+                    b"one\ntw".to_vec(),
+                    b"o\xff\r".to_vec(),
+                    b"\nthree\nfo".to_vec(),
+                    b"ur".to_vec()
+                ],
+                Utf8Policy::SkipLine
+            ),
+            vec!["one\n", "", "three\n", "", "four"]
+        );
+        assert_eq!(
+            decode_all(
+                vec![&b"tw"[..], b"o\xff", b"\xff", b"\nthree\n"],
+                Utf8Policy::SkipLine
+            ),
+            vec!["", "", "", "three\n"]
+        );
+        assert_eq!(
+            decode_all(vec![vec![b'a', b'\n', 240, 159]], Utf8Policy::SkipLine),
+            vec!["a\n"]
+        );
+    }
+}